@@ -0,0 +1,33 @@
+use chrono::NaiveDate;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use islam::salah::{Config, Location, Madhab, Method, PrayerTimes};
+
+fn locations() -> Vec<Location> {
+    (0..1000)
+        .map(|i| Location::new(-45.0 + (i % 90) as f32, -180.0 + (i % 360) as f32))
+        .collect()
+}
+
+fn same_date_many_locations(c: &mut Criterion) {
+    let date = NaiveDate::from_ymd_opt(2021, 4, 9).expect("valid date");
+    let config = Config::new().with(Method::MuslimWorldLeague, Madhab::Shafi);
+    let locations = locations();
+
+    let mut group = c.benchmark_group("same date, 1000 locations");
+    group.bench_function("per location", |b| {
+        b.iter(|| {
+            locations
+                .iter()
+                .map(|&location| PrayerTimes::new(black_box(date), location, config))
+                .collect::<Result<Vec<_>, _>>()
+        })
+    });
+    group.bench_function("shared sun", |b| {
+        b.iter(|| PrayerTimes::for_locations(black_box(date), &locations, config))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, same_date_many_locations);
+criterion_main!(benches);
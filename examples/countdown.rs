@@ -0,0 +1,83 @@
+use std::{thread, time::Duration};
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use islam::{
+    salah::{Config, Location, Madhab, Method, PrayerSchedule, PrayerTimes},
+    Clock, FixedClock,
+};
+
+/// One line of the countdown, `Dohr | Asr in 01:02:03`, with Jumua on Fridays
+fn tick(prayer_times: &PrayerTimes, clock: &impl Clock) -> Result<String, islam::Error> {
+    let current = match prayer_times.current_with(clock) {
+        Ok(prayer) => prayer.name_with(clock)?,
+        Err(_) => "-".to_string(),
+    };
+    let countdown = prayer_times.countdown(clock.now())?;
+    Ok(format!("{current} | {countdown}"))
+}
+
+fn jakarta() -> Result<PrayerSchedule, islam::Error> {
+    let jakarta_city = Location::new(-6.182_34_f32, 106.842_87_f32);
+    let config = Config::new()
+        .with(Method::Singapore, Madhab::Shafi)
+        .with_timezone(7.0);
+    Ok(PrayerSchedule::new(jakarta_city)?.with_config(config))
+}
+
+fn noon() -> Result<NaiveDateTime, islam::Error> {
+    NaiveDate::from_ymd_opt(2021, 4, 9)
+        .and_then(|date| date.and_hms_opt(12, 0, 0))
+        .ok_or(islam::Error::InvalidTime)
+}
+
+fn example() -> Result<(), islam::Error> {
+    let schedule = jakarta()?;
+
+    // a fixed clock always gives the same output
+    let noon = noon()?;
+    let fixed = schedule.on(noon.date()).calculate()?;
+    println!("At {noon}: {}", tick(&fixed, &FixedClock(noon))?);
+
+    // Jakarta's own clock, whatever the machine's time zone
+    let mut prayer_times = schedule.calculate()?;
+    let clock = prayer_times.clock();
+    loop {
+        if prayer_times.date.date() != clock.today() {
+            prayer_times = schedule.on(clock.today()).calculate()?;
+        }
+        print!("\r{}", tick(&prayer_times, &clock)?);
+        std::io::Write::flush(&mut std::io::stdout()).ok();
+        thread::sleep(Duration::from_secs(1));
+    }
+}
+
+fn main() {
+    if let Err(err) = example() {
+        eprintln!("Error: {:?}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use islam::salah::Prayer;
+
+    use super::*;
+
+    #[test]
+    fn tick_at_noon() -> Result<(), islam::Error> {
+        let noon = noon()?;
+        let clock = FixedClock(noon);
+        let prayer_times = jakarta()?.on(noon.date()).calculate()?;
+
+        // 2021-04-09 is a Friday
+        assert_eq!(tick(&prayer_times, &clock)?, "Jumua | Asr in 03:12:14");
+        assert_eq!(prayer_times.next_with(&clock)?, Prayer::Asr);
+
+        let thursday = noon - chrono::Duration::days(1);
+        let clock = FixedClock(thursday);
+        let prayer_times = jakarta()?.on(thursday.date()).calculate()?;
+        assert_eq!(tick(&prayer_times, &clock)?, "Dohr | Asr in 03:12:15");
+        Ok(())
+    }
+}
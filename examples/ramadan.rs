@@ -0,0 +1,49 @@
+use chrono::{Duration, NaiveDate};
+
+use islam::hijri::HijriDate;
+use islam::salah::{Config, Location, Madhab, Method, PrayerSchedule};
+
+fn example() -> Result<(), islam::Error> {
+    // https://www.mapcoordinates.net/en
+    let jakarta_city = Location::new(-6.182_34_f32, 106.842_87_f32);
+    let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+    let schedule = PrayerSchedule::new(jakarta_city)?.with_config(config);
+
+    let start = NaiveDate::from_ymd_opt(2021, 1, 1)
+        .ok_or(islam::Error::InvalidArgument("Invalid time".to_string()))?;
+    let ramadan = (0..366)
+        .filter_map(|day| start.checked_add_signed(Duration::days(day)))
+        .filter(|&date| HijriDate::from_gregorian(date, 0).month == 9);
+
+    println!("Ramadan 1442, Jakarta\n");
+    println!(
+        "{:<12} {:>3} {:>7} {:>7} {:>7}",
+        "Date", "Day", "Imsak", "Fajr", "Iftar"
+    );
+    for (i, date) in ramadan.enumerate() {
+        let hijri = HijriDate::from_gregorian(date, 0);
+        let prayer_times = schedule.on(date).calculate()?;
+        if i == 0 {
+            assert!(
+                prayer_times.imsak < prayer_times.fajr,
+                "imsak must precede fajr"
+            );
+        }
+        println!(
+            "{:<12} {:>3} {:>7} {:>7} {:>7}",
+            date.to_string(),
+            hijri.day,
+            prayer_times.imsak.format("%H:%M").to_string(),
+            prayer_times.fajr.format("%H:%M").to_string(),
+            prayer_times.maghreb.format("%H:%M").to_string(),
+        );
+    }
+
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = example() {
+        eprintln!("Error: {:?}", err);
+    }
+}
@@ -4,7 +4,7 @@ use islam::salah::{Config, Location, Madhab, Method, PrayerSchedule};
 
 fn example() -> Result<(), islam::Error> {
     // https://www.mapcoordinates.net/en
-    let jakarta_city = Location::new(6.182_34_f32, 106.842_87_f32);
+    let jakarta_city = Location::with_timezone(-6.182_34_f32, 106.842_87_f32, 7.0);
     let config = Config::new().with(Method::Egyptian, Madhab::Shafi);
     // Tested against https://www.jadwalsholat.org/
     let prayer_times = PrayerSchedule::new(jakarta_city)?
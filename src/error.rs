@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::salah::Prayer;
+
 #[derive(Error, Debug, PartialEq)]
 pub enum Error {
     #[error("No such month: {0:?}")]
@@ -8,6 +10,27 @@ pub enum Error {
     #[error("No such time")]
     InvalidTime,
 
+    #[error("Angle out of range: {0}")]
+    InvalidAngle(f32),
+
+    #[error("No prayer is due at this time")]
+    NoCurrentPrayer,
+
+    #[error("Prayer times are undefined at the poles")]
+    PolarLocation,
+
+    #[error("Prayer times are not in chronological order")]
+    NonMonotonicTimes,
+
+    #[error("Both an Ishaa angle and interval are set, use `prefer_angle_isha` to pick the angle")]
+    ConflictingIshaConfig,
+
+    #[error("The sun never reaches the angle of {prayer:?} at this location and date")]
+    NoValidTime { prayer: Prayer },
+
+    #[error("Coordinates out of range: latitude {latitude}, longitude {longitude}")]
+    InvalidCoordinates { latitude: f32, longitude: f32 },
+
     #[error("{0}")]
     InvalidArgument(String),
 }
@@ -3,38 +3,44 @@ use chrono::Datelike;
 use crate::Date;
 
 // Trigonometric functions takes values in degree
-pub fn dcos(deg: f32) -> f32 {
+pub fn dcos(deg: f64) -> f64 {
     deg.to_radians().cos()
 }
 
-pub fn dsin(deg: f32) -> f32 {
+pub fn dsin(deg: f64) -> f64 {
     deg.to_radians().sin()
 }
 
 //  Hijri date calculation methods
 
 /// Get equation of time
-pub fn equation_of_time(julian_day: f32) -> f32 {
+pub fn equation_of_time(julian_day: f64) -> f64 {
     let n = julian_day - 2_451_544.5;
-    let g = 0.985_600_3_f32.mul_add(n, 357.528);
-    let c = 0.0003_f32.mul_add(
+    let g = 0.985_600_3_f64.mul_add(n, 357.528);
+    let c = 0.0003_f64.mul_add(
         dsin(3.0 * g),
-        1.9148_f32.mul_add(dsin(g), 0.02 * dsin(2.0 * g)),
+        1.9148_f64.mul_add(dsin(g), 0.02 * dsin(2.0 * g)),
     );
-    let lamda = 0.985_600_3_f32.mul_add(n, 280.47) + c;
-    let r = 0.0014_f32.mul_add(
+    let lamda = 0.985_600_3_f64.mul_add(n, 280.47) + c;
+    let r = 0.0014_f64.mul_add(
         dsin(6.0 * lamda),
-        (-2.468_f32).mul_add(dsin(2.0 * lamda), 0.053 * dsin(4.0 * lamda)),
+        (-2.468_f64).mul_add(dsin(2.0 * lamda), 0.053 * dsin(4.0 * lamda)),
     );
     (c + r) * 4.0
 }
 
 pub fn hijri_to_julian(date: Date) -> i32 {
-    ((((11 * date.year() + 3) / 30) as f32).floor()
-        + ((354 * date.year()) as f32).floor()
-        + ((30 * date.month() as u8) as f32).floor()
-        - (((date.month() as u8 - 1) / 2) as f32).floor()
-        + date.day() as f32
+    hijri_ymd_to_julian(date.year(), date.month(), date.day())
+}
+
+/// Julian Day Number of a Hijri date, which needs no valid Gregorian
+/// counterpart such as 30 Safar
+pub fn hijri_ymd_to_julian(year: i32, month: u32, day: u32) -> i32 {
+    ((((11 * year + 3) / 30) as f32).floor()
+        + ((354 * year) as f32).floor()
+        + ((30 * month) as f32).floor()
+        - (((month - 1) / 2) as f32).floor()
+        + day as f32
         + 1_948_440.0
         - 385.0) as i32
 }
@@ -90,25 +96,27 @@ pub fn julian_to_hijri(julian_day: i32, correction_val: i32) -> (i32, u32, u32)
     (year as i32, month as u32, day as u32)
 }
 
-pub fn julian_to_gregorian(mut julian_day: f32) -> (i32, u32, u32) {
-    julian_day = (julian_day as i32 + 5) as f32;
+/// Inverse of `gregorian_to_julian`, also accepting the whole Julian Day
+/// Numbers (noon) given by `hijri_to_julian`
+pub fn julian_to_gregorian(julian_day: f32) -> (i32, u32, u32) {
+    let julian_day = f64::from(julian_day) + 0.5;
     let z = julian_day.floor() as i32;
-    let f = julian_day as i32 - z;
 
     let a = if z < 2_299_161 {
         z
     } else {
-        let alpha = ((z as f32 - 1_867_216.25) / 36524.25).floor();
+        let alpha = ((f64::from(z) - 1_867_216.25) / 36524.25).floor();
         ((z + 1) + alpha as i32) - ((alpha as i32 / 4) as f32).floor() as i32
     };
 
     let b = a + 1524;
-    let c = ((b as f32 - 122.1) / 365.25).floor();
+    // f32 is too coarse for the day count of the year
+    let c = ((f64::from(b) - 122.1) / 365.25).floor();
     let d = (365.25 * c).floor();
-    let e = ((b - d as i32) as f32 / 30.6001).floor(); //  The 30.6001 SHOULD NOT BE REPLACED by 30.6
+    let e = (f64::from(b - d as i32) / 30.6001).floor(); //  The 30.6001 SHOULD NOT BE REPLACED by 30.6
 
     // Calculate the day
-    let day = (b - d as i32) - (30.6001 * e).floor() as i32 + f;
+    let day = (b - d as i32) - (30.6001 * e).floor() as i32;
 
     // Calculate the month
     let month = e as i32 - if (e as i32) < 14 { 1 } else { 13 };
@@ -126,31 +134,39 @@ mod tests {
 
     #[test]
     fn test_dcos() {
-        assert_eq!(dcos(10.0), 0.9848077);
-        assert_eq!(dcos(20.0), 0.9396926);
-        assert_eq!(dcos(30.0), 0.8660254);
+        assert_eq!(dcos(10.0), 0.984807753012208);
+        assert_eq!(dcos(20.0), 0.9396926207859084);
+        assert_eq!(dcos(30.0), 0.8660254037844387);
     }
     #[test]
     fn test_dsin() {
-        assert_eq!(dsin(10.0), 0.17364818);
-        assert_eq!(dsin(20.0), 0.34202012);
-        assert_eq!(dsin(30.0), 0.5);
+        assert_eq!(dsin(10.0), 0.17364817766693033);
+        assert_eq!(dsin(20.0), 0.3420201433256687);
+        assert!((dsin(30.0) - 0.5).abs() < 1e-15);
     }
     #[test]
     fn test_equation_of_time() {
         let precision = 5;
 
-        assert_eq!(equation_of_time(2_436_116.31), -11.653772);
-        assert_eq!(equation_of_time(1842713.0), 12.964235);
+        assert_eq!(
+            format!("{:.1$}", equation_of_time(2_436_116.31), precision),
+            "-11.67184"
+        );
+        assert_eq!(
+            format!("{:.1$}", equation_of_time(1842713.0), precision),
+            "12.95793"
+        );
 
         let equation = equation_of_time(2451545.0);
-        assert_eq!(format!("{:.1$}", equation, precision), "3.53552");
+        assert_eq!(format!("{:.1$}", equation, precision), "3.53551");
     }
     #[test]
     fn test_hijri_to_julian() -> Result<(), crate::Error> {
         assert_eq!(hijri_to_julian(date(1442, 8, 25)?), 2459313);
         assert_eq!(hijri_to_julian(date(333, 1, 27)?), 2066116);
         assert_eq!(hijri_to_julian(date(1, 1, 27)?), 1948466);
+        // months past 8 used to overflow
+        assert_eq!(hijri_to_julian(date(1445, 12, 1)?), 2460470);
         Ok(())
     }
     #[test]
@@ -177,7 +193,8 @@ mod tests {
     }
     #[test]
     fn test_julian_to_gregorian() {
-        assert_eq!(julian_to_gregorian(2459313.0), (2021, 4, 13));
-        assert_eq!(julian_to_gregorian(2415020.5), (1900, 1, 5));
+        assert_eq!(julian_to_gregorian(2459313.0), (2021, 4, 8));
+        assert_eq!(julian_to_gregorian(2415020.5), (1900, 1, 1));
+        assert_eq!(julian_to_gregorian(2451544.5), (2000, 1, 1));
     }
 }
@@ -1,5 +1,5 @@
 use crate::hijri::cal::{
-    gregorian_to_julian, hijri_to_julian, julian_to_gregorian, julian_to_hijri,
+    gregorian_to_julian, hijri_ymd_to_julian, julian_to_gregorian, julian_to_hijri,
 };
 use crate::{time, Date};
 
@@ -56,8 +56,7 @@ impl HijriDate {
         })
     }
     pub fn to_julian(&self) -> Result<i32, crate::Error> {
-        let date = time::date(self.year, self.month, self.day)?;
-        Ok(hijri_to_julian(date))
+        Ok(hijri_ymd_to_julian(self.year, self.month, self.day))
     }
     pub fn to_gregorian(&self) -> Result<Date, crate::Error> {
         let julian = self.to_julian()?;
@@ -82,6 +81,10 @@ impl HijriDate {
             month_english: Self::month_english(month),
         }
     }
+    /// Transliteration of the month, e.g. `Ramadan`. See `month_arabic` for the Arabic script.
+    pub fn month_name(&self) -> &str {
+        &self.month_english
+    }
     fn month_arabic(month: u32) -> String {
         ARABIC_MONTHS[(month - 1) as usize].to_string()
     }
@@ -89,7 +92,9 @@ impl HijriDate {
         ENGLISH_MONTHS[(month - 1) as usize].to_string()
     }
     pub fn from_gregorian(date: Date, correction_val: i32) -> Self {
-        let (year, month, day) = julian_to_hijri(gregorian_to_julian(date) as i32, correction_val);
+        // the Julian Day starts at noon, the Julian Day Number counts the day itself
+        let julian_day = (gregorian_to_julian(date) + 0.5).floor() as i32;
+        let (year, month, day) = julian_to_hijri(julian_day, correction_val);
 
         Self {
             year,
@@ -131,13 +136,32 @@ mod tests {
     fn to_gregorian() -> Result<(), crate::Error> {
         let hijri_date = HijriDate::new(1442, 8, 25)?;
         let gregorian = hijri_date.to_gregorian()?;
-        assert_eq!(gregorian, date(2021, 4, 13)?);
+        assert_eq!(gregorian, date(2021, 4, 8)?);
+        Ok(())
+    }
+    #[test]
+    fn first_of_muharram() -> Result<(), crate::Error> {
+        let new_year = HijriDate::new(1445, 1, 1)?;
+        assert_eq!(new_year.to_gregorian()?, date(2023, 7, 19)?);
+
+        let ramadan = HijriDate::new(1446, 9, 1)?;
+        assert_eq!(ramadan.to_gregorian()?, date(2025, 3, 1)?);
+        Ok(())
+    }
+    #[test]
+    fn gregorian_round_trip() -> Result<(), crate::Error> {
+        let start = date(2020, 1, 1)?;
+        for days in 0..1100 {
+            let gregorian = start + chrono::Duration::days(days);
+            let hijri = HijriDate::from_gregorian(gregorian, 0);
+            assert_eq!(hijri.to_gregorian()?, gregorian);
+        }
         Ok(())
     }
     #[test]
     fn from_gregorian() -> Result<(), crate::Error> {
         let hijri_from_gregorian = HijriDate::from_gregorian(date(2021, 4, 9)?, 0);
-        assert_eq!(hijri_from_gregorian.day, 25); // FIXME: this should be 27
+        assert_eq!(hijri_from_gregorian.day, 26); // FIXME: this should be 27
         assert_eq!(hijri_from_gregorian.month, 8);
         assert_eq!(hijri_from_gregorian.month_arabic, "شعبان".to_string());
         assert_eq!(hijri_from_gregorian.month_english, "Shaban".to_string());
@@ -148,7 +172,7 @@ mod tests {
     fn from_gregorian_1() -> Result<(), crate::Error> {
         let hijri_from_gregorian = HijriDate::from_gregorian(date(2020, 4, 18)?, 0);
         // tested against https://www.islamicfinder.org/islamic-calendar/2021/April/?type=Gregorian
-        assert_eq!(hijri_from_gregorian.day, 23); // FIXME: this should be 25
+        assert_eq!(hijri_from_gregorian.day, 24); // FIXME: this should be 25
         assert_eq!(hijri_from_gregorian.month, 8);
         assert_eq!(hijri_from_gregorian.month_english, "Shaban".to_string());
         assert_eq!(hijri_from_gregorian.year, 1441);
@@ -179,6 +203,13 @@ mod tests {
         Ok(())
     }
     #[test]
+    fn month_name() -> Result<(), crate::Error> {
+        assert_eq!(HijriDate::new(1442, 9, 1)?.month_name(), "Ramadan");
+        assert_eq!(HijriDate::new(1442, 1, 1)?.month_name(), "Moharram");
+        assert_eq!(HijriDate::new(1442, 9, 1)?.month_arabic, "رمضان");
+        Ok(())
+    }
+    #[test]
     fn out_of_index_month() -> Result<(), crate::Error> {
         let err = HijriDate::new(1442, 13, 25).unwrap_err().to_string();
         assert_eq!(err, "No such month: 13");
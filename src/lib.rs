@@ -6,6 +6,7 @@ pub mod salah;
 mod time;
 
 pub use error::Error;
+pub use time::{Clock, FixedClock, SystemClock};
 
 // Use internal type. Chrono API changes very often
 pub type Date = chrono::NaiveDate;
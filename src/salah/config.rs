@@ -1,12 +1,120 @@
-use crate::salah::{madhab::Madhab, method::Method};
+use std::ops::RangeInclusive;
+
+use crate::salah::{madhab::Madhab, method::Method, prayer::Prayer};
+
+/// Depression angles that still give meaningful Fajr and Ishaa times
+const ANGLE_RANGE: RangeInclusive<f32> = 0.0..=25.0;
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IshaInterval {
     pub all_year: f32,
     pub ramdan: f32,
 }
 
+/// Minutes before each prayer to send a reminder
+#[derive(PartialEq, Debug, Default, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NotificationLeads {
+    pub fajr: u32,
+    pub sherook: u32,
+    pub dohr: u32,
+    pub asr: u32,
+    pub maghreb: u32,
+    pub ishaa: u32,
+}
+
+impl NotificationLeads {
+    /// Reminder lead of the given prayer, in minutes
+    pub const fn minutes(&self, prayer: Prayer) -> u32 {
+        match prayer {
+            Prayer::Fajr => self.fajr,
+            Prayer::Sherook => self.sherook,
+            Prayer::Dohr => self.dohr,
+            Prayer::Asr => self.asr,
+            Prayer::Maghreb => self.maghreb,
+            Prayer::Ishaa => self.ishaa,
+        }
+    }
+}
+
+/// Signed minutes added to each computed prayer time, as published by a mosque
+#[derive(PartialEq, Debug, Default, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Adjustments {
+    pub fajr: i32,
+    pub sherook: i32,
+    pub dohr: i32,
+    pub asr: i32,
+    pub maghreb: i32,
+    pub ishaa: i32,
+}
+
+impl Adjustments {
+    /// Adjustment of the given prayer, in minutes
+    pub const fn minutes(&self, prayer: Prayer) -> i32 {
+        match prayer {
+            Prayer::Fajr => self.fajr,
+            Prayer::Sherook => self.sherook,
+            Prayer::Dohr => self.dohr,
+            Prayer::Asr => self.asr,
+            Prayer::Maghreb => self.maghreb,
+            Prayer::Ishaa => self.ishaa,
+        }
+    }
+    /// Adjustment of the given prayer, in seconds
+    pub fn seconds(&self, prayer: Prayer) -> f32 {
+        (self.minutes(prayer) * 60) as f32
+    }
+}
+
+/// Where the Ishaa window ends
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IshaaEnd {
+    /// at tomorrow's Fajr
+    Fajr,
+    /// at the middle of the night
+    Midnight,
+}
+
+/// Where the Tahajjud window begins
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TahajjudStart {
+    /// right after Ishaa
+    Ishaa,
+    /// at the middle of the night
+    Midnight,
+    /// at the beginning of the last third of the night
+    LastThirdOfNight,
+}
+
+/// Fallback for Fajr and Ishaa where the sun never gets low enough for their angle
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HighLatitudeRule {
+    /// both at the middle of the night
+    MiddleOfNight,
+    /// a seventh of the night before sunrise and after sunset
+    SeventhOfNight,
+    /// `angle / 60` of the night before sunrise and after sunset
+    TwilightAngle,
+}
+
+impl HighLatitudeRule {
+    /// Part of the night between sunset and Ishaa, or Fajr and sunrise
+    pub fn portion(self, angle: f32) -> f32 {
+        match self {
+            Self::MiddleOfNight => 1.0 / 2.0,
+            Self::SeventhOfNight => 1.0 / 7.0,
+            Self::TwilightAngle => angle / 60.0,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Config {
     pub fajr_angle: f32,
     pub ishaa_angle: f32,
@@ -18,6 +126,47 @@ pub struct Config {
     pub is_summer: bool,
     /// minutes after Maghreb
     pub isha_interval: IshaInterval,
+    /// measure asr from the sun's lower limb instead of its center
+    pub asr_lower_limb: bool,
+    /// beginning of the Tahajjud window
+    pub tahajjud_start: TahajjudStart,
+    /// end of the Ishaa window
+    pub ishaa_end: IshaaEnd,
+    /// tilt of the earth's axis, in degrees
+    pub obliquity: f32,
+    /// reminder lead for each prayer
+    pub notification_leads: NotificationLeads,
+    /// UTC offset in hours, for locations without their own
+    pub timezone: Option<f32>,
+    /// reject times that are not in chronological order
+    pub strict_ordering: bool,
+    /// minutes after the earliest asr at which it is preferably prayed
+    pub asr_preferred_offset: u32,
+    /// use `ishaa_angle` even when an interval is set
+    pub prefer_angle_isha: bool,
+    /// asr shadow length in object lengths, overriding the madhab
+    pub shadow_factor: Option<u8>,
+    /// degrees below the horizon for Maghreb, instead of sunset
+    pub maghreb_angle: Option<f32>,
+    /// fallback when Fajr or Ishaa has no valid time
+    pub high_latitude_rule: Option<HighLatitudeRule>,
+    /// also compute the Hanafi asr, whatever the madhab
+    pub both_asr: bool,
+    /// minutes between Imsak and Fajr
+    pub imsak_minutes: u32,
+    /// minutes between sunrise and Ishraq
+    pub ishraq_minutes: u32,
+    /// minutes between sunrise and Duha
+    pub duha_minutes: u32,
+    /// manual offset of each prayer
+    pub adjustments: Adjustments,
+    /// days added to the computed Hijri date, to follow the local moon sighting
+    pub hijri_adjustment: i32,
+    /// fixed sun declination in degrees, instead of the astronomical one
+    pub declination_override: Option<f32>,
+    /// IANA time zone, giving the UTC offset and daylight saving of each date
+    #[cfg(feature = "tz")]
+    pub zone: Option<chrono_tz::Tz>,
 }
 
 impl Default for Config {
@@ -39,6 +188,27 @@ impl Config {
                 all_year: 0.0,
                 ramdan: 0.0,
             },
+            asr_lower_limb: false,
+            tahajjud_start: TahajjudStart::Midnight,
+            ishaa_end: IshaaEnd::Fajr,
+            obliquity: 23.44,
+            notification_leads: NotificationLeads::default(),
+            timezone: None,
+            strict_ordering: false,
+            asr_preferred_offset: 0,
+            prefer_angle_isha: false,
+            shadow_factor: None,
+            maghreb_angle: None,
+            high_latitude_rule: None,
+            both_asr: false,
+            imsak_minutes: 10,
+            ishraq_minutes: 15,
+            duha_minutes: 20,
+            adjustments: Adjustments::default(),
+            hijri_adjustment: 0,
+            declination_override: None,
+            #[cfg(feature = "tz")]
+            zone: None,
         }
     }
     pub fn with(&self, method: Method, madhab: Madhab) -> Self {
@@ -56,15 +226,143 @@ impl Config {
         self.ishaa_angle = isha;
         self
     }
-    pub fn is_summer(mut self, is_summer: bool) -> Self {
-        self.is_summer = is_summer;
+    /// Fajr and Ishaa depression angles, in degrees below the horizon, for
+    /// conventions no named method covers. Drops any Ishaa interval.
+    pub fn with_custom_angles(mut self, fajr: f32, isha: f32) -> Self {
+        self.isha_interval = IshaInterval {
+            all_year: 0.0,
+            ramdan: 0.0,
+        };
+        self.angle(fajr, isha).method(Method::Custom)
+    }
+    /// Fajr and Ishaa angle, rejecting values outside 0–25°
+    pub fn with_angles(self, fajr: f32, isha: f32) -> Result<Self, crate::Error> {
+        let config = self.angle(fajr, isha);
+        config.validate()?;
+        Ok(config)
+    }
+    /// Check that the angles are within a sensible range
+    pub fn validate(&self) -> Result<(), crate::Error> {
+        for angle in [self.fajr_angle, self.ishaa_angle] {
+            if !ANGLE_RANGE.contains(&angle) {
+                return Err(crate::Error::InvalidAngle(angle));
+            }
+        }
+        let has_interval = self.isha_interval.all_year > 0.0;
+        if has_interval && self.ishaa_angle > 0.0 && !self.prefer_angle_isha {
+            return Err(crate::Error::ConflictingIshaConfig);
+        }
+        Ok(())
+    }
+    /// Daylight saving time, adds one hour to every computed time
+    pub fn with_summer_time(mut self, summer_time: bool) -> Self {
+        self.is_summer = summer_time;
         self
     }
+    pub fn is_summer(self, is_summer: bool) -> Self {
+        self.with_summer_time(is_summer)
+    }
+    /// Ishaa a fixed time after Maghreb. Takes precedence over the angle,
+    /// which it resets; setting an angle afterwards needs `prefer_angle_isha`.
     pub fn isha_interval(mut self, isha_interval: IshaInterval) -> Self {
         self.ishaa_angle = 0.0;
         self.isha_interval = isha_interval;
         self
     }
+    /// Asr when the shadow is `factor` times the object length plus its noon shadow.
+    /// A factor of 0 puts asr at solar noon, which is only of theoretical interest.
+    pub fn with_shadow_factor(mut self, factor: u8) -> Self {
+        self.shadow_factor = Some(factor);
+        self
+    }
+    /// Maghreb once the sun is `depression` degrees below the horizon, as in Shia
+    /// methods. Ishaa intervals and the night times follow it.
+    pub fn with_maghreb_angle(mut self, depression: f32) -> Self {
+        self.maghreb_angle = Some(depression);
+        self
+    }
+    /// Fallback for Fajr and Ishaa at latitudes where their angle is never reached
+    pub fn with_high_latitude_rule(mut self, rule: HighLatitudeRule) -> Self {
+        self.high_latitude_rule = Some(rule);
+        self
+    }
+    /// Fill `asr_hanafi` next to the madhab's asr, for apps showing both schools
+    pub fn with_both_asr(mut self, both_asr: bool) -> Self {
+        self.both_asr = both_asr;
+        self
+    }
+    /// Imsak `minutes` before Fajr, instead of the usual ten
+    pub fn with_imsak_minutes(mut self, minutes: u32) -> Self {
+        self.imsak_minutes = minutes;
+        self
+    }
+    /// Ishraq `minutes` after sunrise, instead of the usual fifteen
+    pub fn with_ishraq_minutes(mut self, minutes: u32) -> Self {
+        self.ishraq_minutes = minutes;
+        self
+    }
+    /// Duha `minutes` after sunrise, instead of the usual twenty
+    pub fn with_duha_minutes(mut self, minutes: u32) -> Self {
+        self.duha_minutes = minutes;
+        self
+    }
+    /// Move each prayer by a fixed number of minutes, e.g. to match a local mosque
+    pub fn with_adjustments(mut self, adjustments: Adjustments) -> Self {
+        self.adjustments = adjustments;
+        self
+    }
+    /// Shift the Hijri date by `days`, for regions that sight the moon earlier or later
+    pub fn with_hijri_adjustment(mut self, days: i32) -> Self {
+        self.hijri_adjustment = days;
+        self
+    }
+    /// Compute Ishaa from the angle when both an angle and an interval are set
+    pub fn prefer_angle_isha(mut self, prefer_angle_isha: bool) -> Self {
+        self.prefer_angle_isha = prefer_angle_isha;
+        self
+    }
+    /// Use the sun's lower limb rather than its center for the asr shadow
+    pub fn asr_lower_limb(mut self, lower_limb: bool) -> Self {
+        self.asr_lower_limb = lower_limb;
+        self
+    }
+    pub fn tahajjud_start(mut self, tahajjud_start: TahajjudStart) -> Self {
+        self.tahajjud_start = tahajjud_start;
+        self
+    }
+    /// Axial tilt of the planet, to explore how seasons shape the prayer times
+    pub fn with_obliquity(mut self, degrees: f32) -> Self {
+        self.obliquity = degrees;
+        self
+    }
+    /// IANA time zone, overriding `with_timezone` and `with_summer_time`
+    #[cfg(feature = "tz")]
+    pub fn with_zone(mut self, zone: chrono_tz::Tz) -> Self {
+        self.zone = Some(zone);
+        self
+    }
+    /// UTC offset in hours, fractional for zones such as `5.5`
+    pub fn with_timezone(mut self, timezone: f32) -> Self {
+        self.timezone = Some(timezone);
+        self
+    }
+    pub fn with_notification_leads(mut self, notification_leads: NotificationLeads) -> Self {
+        self.notification_leads = notification_leads;
+        self
+    }
+    pub fn ishaa_end(mut self, ishaa_end: IshaaEnd) -> Self {
+        self.ishaa_end = ishaa_end;
+        self
+    }
+    pub fn with_asr_preferred_offset(mut self, minutes: u32) -> Self {
+        self.asr_preferred_offset = minutes;
+        self
+    }
+    /// Fail with `NonMonotonicTimes` instead of returning out-of-order times
+    pub fn strict_ordering(mut self, strict_ordering: bool) -> Self {
+        self.strict_ordering = strict_ordering;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -78,4 +376,51 @@ mod tests {
 
         assert_eq!(config.method, Method::Egyptian);
     }
+
+    #[test]
+    fn accepted_angle() -> Result<(), crate::Error> {
+        let config = Config::new().with_angles(18.0, 18.0)?;
+
+        assert_eq!(config.fajr_angle, 18.0);
+        assert_eq!(config.ishaa_angle, 18.0);
+        Ok(())
+    }
+
+    #[test]
+    fn rejected_angle() {
+        let err = Config::new().with_angles(95.0, 18.0).unwrap_err();
+        assert_eq!(err, crate::Error::InvalidAngle(95.0));
+
+        let err = Config::new().with_angles(18.0, -1.0).unwrap_err();
+        assert_eq!(err, crate::Error::InvalidAngle(-1.0));
+    }
+
+    #[test]
+    fn custom_angles() -> Result<(), crate::Error> {
+        let config = Method::UmmAlQura.configs().with_custom_angles(18.5, 16.5);
+
+        assert_eq!(config.method, Method::Custom);
+        assert_eq!((config.fajr_angle, config.ishaa_angle), (18.5, 16.5));
+        config.validate()
+    }
+    #[test]
+    fn isha_precedence() -> Result<(), crate::Error> {
+        let interval = IshaInterval {
+            all_year: 90.0,
+            ramdan: 120.0,
+        };
+        // the interval resets the angle
+        let config = Config::new().angle(18.0, 17.0).isha_interval(interval);
+        assert_eq!(config.ishaa_angle, 0.0);
+        config.validate()?;
+
+        let conflicting = config.angle(18.0, 17.0);
+        assert_eq!(
+            conflicting.validate(),
+            Err(crate::Error::ConflictingIshaConfig)
+        );
+
+        conflicting.prefer_angle_isha(true).validate()?;
+        Ok(())
+    }
 }
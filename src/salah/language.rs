@@ -0,0 +1,43 @@
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Language {
+    English,
+    Indonesian,
+}
+
+impl Language {
+    /// Spell out a duration, e.g. "2 hours 15 minutes"
+    pub fn duration(self, hours: u32, minutes: u32) -> String {
+        let (hour, minute) = match self {
+            Self::English => (
+                if hours == 1 { "hour" } else { "hours" },
+                if minutes == 1 { "minute" } else { "minutes" },
+            ),
+            Self::Indonesian => ("jam", "menit"),
+        };
+        match (hours, minutes) {
+            (0, minutes) => format!("{minutes} {minute}"),
+            (hours, 0) => format!("{hours} {hour}"),
+            (hours, minutes) => format!("{hours} {hour} {minutes} {minute}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english_duration() {
+        assert_eq!(Language::English.duration(2, 15), "2 hours 15 minutes");
+        assert_eq!(Language::English.duration(1, 1), "1 hour 1 minute");
+        assert_eq!(Language::English.duration(0, 45), "45 minutes");
+        assert_eq!(Language::English.duration(3, 0), "3 hours");
+    }
+
+    #[test]
+    fn indonesian_duration() {
+        assert_eq!(Language::Indonesian.duration(2, 15), "2 jam 15 menit");
+        assert_eq!(Language::Indonesian.duration(1, 1), "1 jam 1 menit");
+        assert_eq!(Language::Indonesian.duration(0, 45), "45 menit");
+    }
+}
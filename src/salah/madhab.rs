@@ -1,4 +1,5 @@
 #[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Madhab {
     /// Jomhor (Shafii, Maliki & Hambali)
     Shafi = 1,
@@ -10,6 +11,10 @@ impl Madhab {
     pub const fn shadow(self) -> i32 {
         self as i32
     }
+    /// Asr shadow length, in object lengths beyond the noon shadow
+    pub const fn shadow_factor(self) -> u8 {
+        self.shadow() as u8
+    }
 }
 
 #[cfg(test)]
@@ -29,4 +34,10 @@ mod tests {
 
         assert_eq!(hanafi.shadow(), 2);
     }
+
+    #[test]
+    fn shadow_factor() {
+        assert_eq!(Madhab::Shafi.shadow_factor(), 1);
+        assert_eq!(Madhab::Hanafi.shadow_factor(), 2);
+    }
 }
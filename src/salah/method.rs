@@ -1,6 +1,7 @@
 use crate::salah::config::{Config, IshaInterval};
 
 #[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Method {
     /// University of Islamic Sciences, Karachi (UISK)
     /// Ministry of Religious Affaires, Tunisia
@@ -33,11 +34,39 @@ pub enum Method {
     /// Spiritual Administration of Muslims of Russia
     Russia,
 
+    /// Institute of Geophysics, University of Tehran
+    /// Maghreb when the sun is 4.5° below the horizon
+    Tehran,
+
+    /// Shia Ithna-Ashari, Leva Institute, Qum
+    /// Maghreb when the sun is 4° below the horizon
+    Jafari,
+
     /// Fixed Ishaa Time Interval, 90min
     FixedInterval,
+
+    /// Angles set with `Config::with_custom_angles`
+    Custom,
 }
 
 impl Method {
+    /// Display name of the method
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Karachi => "University of Islamic Sciences, Karachi",
+            Self::MuslimWorldLeague => "Muslim World League",
+            Self::Egyptian => "Egyptian General Authority of Survey",
+            Self::UmmAlQura => "Umm al-Qura University, Makkah",
+            Self::NorthAmerica => "Islamic Society of North America",
+            Self::French => "French Muslims",
+            Self::Singapore => "Islamic Religious Council of Singapore",
+            Self::Russia => "Spiritual Administration of Muslims of Russia",
+            Self::Tehran => "Institute of Geophysics, University of Tehran",
+            Self::Jafari => "Shia Ithna-Ashari, Leva Institute, Qum",
+            Self::FixedInterval => "Fixed Ishaa Time Interval",
+            Self::Custom => "Custom",
+        }
+    }
     /// Generate configs
     pub fn configs(self) -> Config {
         match self {
@@ -57,6 +86,14 @@ impl Method {
             Self::French => Config::new().angle(12.0, 12.0).method(self),
             Self::Singapore => Config::new().angle(20.0, 18.0).method(self),
             Self::Russia => Config::new().angle(16.0, 15.0).method(self),
+            Self::Tehran => Config::new()
+                .angle(17.7, 14.0)
+                .method(self)
+                .with_maghreb_angle(4.5),
+            Self::Jafari => Config::new()
+                .angle(16.0, 14.0)
+                .method(self)
+                .with_maghreb_angle(4.0),
             Self::FixedInterval => {
                 Config::new()
                     .angle(19.5, 0.0)
@@ -66,6 +103,7 @@ impl Method {
                         ramdan: 120.0,
                     })
             }
+            Self::Custom => Config::new().method(self),
         }
     }
 }
@@ -73,6 +111,7 @@ impl Method {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::salah::madhab::Madhab;
 
     #[test]
     fn configs_for_muslim_world_league() {
@@ -80,6 +119,9 @@ mod tests {
         let params = method.configs();
 
         assert_eq!(params.method, Method::MuslimWorldLeague);
+
+        let config = Config::new().with(Method::MuslimWorldLeague, Madhab::Shafi);
+        assert_eq!((config.fajr_angle, config.ishaa_angle), (18.0, 17.0));
     }
 
     #[test]
@@ -88,5 +130,32 @@ mod tests {
         let params = method.configs();
 
         assert_eq!(params.method, Method::Egyptian);
+        assert_eq!((params.fajr_angle, params.ishaa_angle), (19.5, 17.5));
+        assert_eq!(params.isha_interval.all_year, 0.0);
+    }
+
+    #[test]
+    fn configs_for_north_america() {
+        let config = Config::new().with(Method::NorthAmerica, Madhab::Shafi);
+
+        assert_eq!(config.method, Method::NorthAmerica);
+        assert_eq!((config.fajr_angle, config.ishaa_angle), (15.0, 15.0));
+    }
+
+    #[test]
+    fn configs_for_jafari() {
+        let config = Config::new().with(Method::Jafari, Madhab::Shafi);
+
+        assert_eq!((config.fajr_angle, config.ishaa_angle), (16.0, 14.0));
+        assert_eq!(config.maghreb_angle, Some(4.0));
+        assert_eq!(config.madhab.shadow(), 1);
+    }
+    #[test]
+    fn method_name() {
+        assert_eq!(Method::MuslimWorldLeague.name(), "Muslim World League");
+        assert_eq!(
+            Method::Singapore.name(),
+            "Islamic Religious Council of Singapore"
+        );
     }
 }
@@ -1,14 +1,21 @@
 #![allow(clippy::excessive_precision)]
 
 mod config;
+mod language;
 mod madhab;
 mod method;
 mod prayer;
 mod times;
 
 // shorter access for library consumer
-pub use config::Config;
+pub use config::{
+    Adjustments, Config, HighLatitudeRule, IshaaEnd, NotificationLeads, TahajjudStart,
+};
+pub use language::Language;
 pub use madhab::Madhab;
 pub use method::Method;
 pub use prayer::Prayer;
-pub use times::{Location, PrayerSchedule, PrayerTimes};
+pub use times::{
+    compute_grid, Countdown, GridCell, Location, LocationClock, PrayerSchedule, PrayerTimes,
+    SunnahTimes, TwilightKind, Warning, SUNSET_ANGLE,
+};
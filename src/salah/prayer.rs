@@ -1,9 +1,12 @@
+use std::fmt;
+
 use chrono::{Datelike, Weekday};
 
-use crate::time::today;
+use crate::{Clock, SystemClock};
 
 // only obligatory prayer
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Prayer {
     Fajr,
     Sherook,
@@ -15,11 +18,15 @@ pub enum Prayer {
 
 impl Prayer {
     pub fn name(self) -> Result<String, crate::Error> {
+        self.name_with(&SystemClock)
+    }
+    /// Name of the prayer, Dohr being Jumua on the Friday of the given clock
+    pub fn name_with(self, clock: &impl Clock) -> Result<String, crate::Error> {
         let prayer_name = match self {
             Self::Fajr => "Fajr",
             Self::Sherook => "Sherook",
             Self::Dohr => {
-                if today().weekday() == Weekday::Fri {
+                if clock.today().weekday() == Weekday::Fri {
                     "Jumua"
                 } else {
                     "Dohr"
@@ -31,11 +38,51 @@ impl Prayer {
         };
         Ok(prayer_name.to_string())
     }
+    /// Name in Arabic script
+    pub const fn name_arabic(self) -> &'static str {
+        match self {
+            Self::Fajr => "الفجر",
+            Self::Sherook => "الشروق",
+            Self::Dohr => "الظهر",
+            Self::Asr => "العصر",
+            Self::Maghreb => "المغرب",
+            Self::Ishaa => "العشاء",
+        }
+    }
+    /// Parse an English transliteration or Arabic name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        let name = name.strip_prefix("ال").unwrap_or(&name);
+        match name {
+            "fajr" | "fajar" | "subh" | "فجر" => Some(Self::Fajr),
+            "sherook" | "shuruq" | "shurooq" | "sunrise" | "شروق" => Some(Self::Sherook),
+            "dohr" | "dhuhr" | "zuhr" | "duhr" | "ظهر" => Some(Self::Dohr),
+            "asr" | "عصر" => Some(Self::Asr),
+            "maghreb" | "maghrib" | "مغرب" => Some(Self::Maghreb),
+            "ishaa" | "isha" | "esha" | "عشاء" => Some(Self::Ishaa),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Prayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Fajr => "Fajr",
+            Self::Sherook => "Sunrise",
+            Self::Dohr => "Dhuhr",
+            Self::Asr => "Asr",
+            Self::Maghreb => "Maghrib",
+            Self::Ishaa => "Isha",
+        };
+        f.write_str(name)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{time, time::today, FixedClock};
 
     #[test]
     fn prayer_name() -> Result<(), crate::Error> {
@@ -54,4 +101,47 @@ mod tests {
 
         Ok(())
     }
+    #[test]
+    fn prayer_name_with_clock() -> Result<(), crate::Error> {
+        let noon = |day| {
+            time::date(2021, 4, day)?
+                .and_hms_opt(12, 0, 0)
+                .ok_or(crate::Error::InvalidTime)
+        };
+        // 2021-04-09 is a Friday
+        let friday = FixedClock(noon(9)?);
+        let thursday = FixedClock(noon(8)?);
+
+        assert_eq!(Prayer::Dohr.name_with(&friday)?, "Jumua");
+        assert_eq!(Prayer::Dohr.name_with(&thursday)?, "Dohr");
+        assert_eq!(Prayer::Asr.name_with(&friday)?, "Asr");
+        Ok(())
+    }
+    #[test]
+    fn display() {
+        let names: Vec<String> = [
+            Prayer::Fajr,
+            Prayer::Sherook,
+            Prayer::Dohr,
+            Prayer::Asr,
+            Prayer::Maghreb,
+            Prayer::Ishaa,
+        ]
+        .iter()
+        .map(|prayer| prayer.to_string())
+        .collect();
+        assert_eq!(
+            names,
+            ["Fajr", "Sunrise", "Dhuhr", "Asr", "Maghrib", "Isha"]
+        );
+        assert_eq!(Prayer::Maghreb.name_arabic(), "المغرب");
+    }
+    #[test]
+    fn from_name() {
+        assert_eq!(Prayer::from_name("maghrib"), Some(Prayer::Maghreb));
+        assert_eq!(Prayer::from_name("Maghreb"), Some(Prayer::Maghreb));
+        assert_eq!(Prayer::from_name("المغرب"), Some(Prayer::Maghreb));
+        assert_eq!(Prayer::from_name("ISHA"), Some(Prayer::Ishaa));
+        assert_eq!(Prayer::from_name("dinner"), None);
+    }
 }
@@ -1,34 +1,232 @@
-use std::f32::consts::PI;
+use std::{
+    collections::BTreeMap,
+    f64::consts::PI,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Index, Range, RangeInclusive},
+};
 
-use chrono::{Datelike, Duration, Local};
+use chrono::{Datelike, Duration};
 
 use crate::{
     hijri::{cal, HijriDate},
-    salah::{config::Config, prayer::Prayer},
-    time, Date, DateTime,
+    salah::{
+        config::{Config, IshaaEnd, TahajjudStart},
+        language::Language,
+        madhab::Madhab,
+        method::Method,
+        prayer::Prayer,
+    },
+    time, Clock, Date, DateTime,
 };
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+/// Beyond this latitude the hour angle is undefined
+const MAX_LATITUDE: f32 = 89.9;
+
+/// Zenith angle of the sun's center at sunrise and sunset, in degrees:
+/// 90° plus 34' of refraction and 16' of semidiameter
+pub const SUNSET_ANGLE: f32 = 90.83333;
+
+/// Apparent radius of the sun's disc, in degrees
+const SUN_SEMIDIAMETER: f32 = 0.26667;
+
+/// Coordinates of the Kaaba in Makkah
+const KAABA: (f64, f64) = (21.422_487, 39.826_206);
+
+/// Mean radius of the earth, in kilometers
+const EARTH_RADIUS: f64 = 6371.0;
+
+/// Column names of the CSV timetable rows
+const CSV_HEADER: &str = "date,fajr,sunrise,dhuhr,asr,maghrib,isha";
+
+/// The daily prayers, in chronological order
+const PRAYERS: [Prayer; 6] = [
+    Prayer::Fajr,
+    Prayer::Sherook,
+    Prayer::Dohr,
+    Prayer::Asr,
+    Prayer::Maghreb,
+    Prayer::Ishaa,
+];
+
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Location {
     /// geographical latitude of the given location
     latitude: f32,
     /// geographical longitude of the given location
     longitude: f32,
+    /// UTC offset in hours, when given explicitly
+    timezone: Option<f32>,
+    /// height above sea level, in meters
+    #[cfg_attr(feature = "serde", serde(default))]
+    elevation_meters: f32,
 }
 
 impl Location {
+    /// Location whose timezone is approximated from its longitude, see `timezone`
     pub fn new(latitude: f32, longitude: f32) -> Self {
         Self {
             latitude,
             longitude,
+            timezone: None,
+            elevation_meters: 0.0,
+        }
+    }
+    /// Like `new`, but rejects a latitude outside ±90° or a longitude outside ±180°
+    pub fn try_new(latitude: f32, longitude: f32) -> Result<Self, crate::Error> {
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return Err(crate::Error::InvalidCoordinates {
+                latitude,
+                longitude,
+            });
         }
+        Ok(Self::new(latitude, longitude))
+    }
+    /// Location with an explicit UTC offset in hours, fractional for zones such as `5.5`
+    pub fn with_timezone(latitude: f32, longitude: f32, timezone: f32) -> Self {
+        Self {
+            timezone: Some(timezone),
+            ..Self::new(latitude, longitude)
+        }
+    }
+    /// Height above sea level, which lowers the horizon for Sherook and Maghreb
+    pub fn with_elevation(mut self, meters: f32) -> Self {
+        self.elevation_meters = meters.max(0.0);
+        self
+    }
+    pub const fn latitude(&self) -> f32 {
+        self.latitude
+    }
+    pub const fn longitude(&self) -> f32 {
+        self.longitude
+    }
+    pub const fn elevation_meters(&self) -> f32 {
+        self.elevation_meters
+    }
+    /// Zenith angle of the sun at sunrise and sunset, including the dip of the horizon
+    pub fn sunset_angle(&self) -> f32 {
+        SUNSET_ANGLE + 0.0347 * self.elevation_meters.sqrt()
+    }
+    /// UTC offset in hours. Without an explicit one, it is the nautical zone of the
+    /// longitude, `round(longitude / 15)`, which ignores political borders.
+    pub fn timezone(&self) -> f32 {
+        self.timezone
+            .unwrap_or_else(|| (self.longitude / 15.0).round())
+    }
+    /// Whether both locations are the same up to 5 decimals (about one meter)
+    pub fn approx_eq(&self, other: &Self) -> bool {
+        self.coordinates_key() == other.coordinates_key()
+    }
+    /// Direction of the Kaaba, in degrees clockwise from true north
+    pub fn qibla(&self) -> f32 {
+        let (latitude, longitude) = self.radians();
+        let (kaaba_latitude, kaaba_longitude) = (KAABA.0.to_radians(), KAABA.1.to_radians());
+        let delta = kaaba_longitude - longitude;
+        let bearing = delta
+            .sin()
+            .atan2(latitude.cos() * kaaba_latitude.tan() - latitude.sin() * delta.cos());
+        bearing.to_degrees().rem_euclid(360.0) as f32
+    }
+    /// Qibla rounded to `decimals` places, for display
+    pub fn qibla_rounded(&self, decimals: u32) -> f32 {
+        round(self.qibla(), decimals)
+    }
+    /// Great-circle distance to the Kaaba, in kilometers
+    pub fn qibla_distance(&self) -> f32 {
+        let (latitude, longitude) = self.radians();
+        let (kaaba_latitude, kaaba_longitude) = (KAABA.0.to_radians(), KAABA.1.to_radians());
+        let haversine = ((kaaba_latitude - latitude) / 2.0).sin().powi(2)
+            + latitude.cos()
+                * kaaba_latitude.cos()
+                * ((kaaba_longitude - longitude) / 2.0).sin().powi(2);
+        // rounding can push the antipode just past 1
+        (2.0 * EARTH_RADIUS * haversine.min(1.0).sqrt().asin()) as f32
+    }
+    /// Same as `qibla_distance`
+    pub fn distance_to_kaaba(&self) -> f32 {
+        self.qibla_distance()
+    }
+    /// Distance to the Kaaba rounded to `decimals` places, for display
+    pub fn distance_to_kaaba_rounded(&self, decimals: u32) -> f32 {
+        round(self.distance_to_kaaba(), decimals)
+    }
+    fn radians(&self) -> (f64, f64) {
+        (
+            f64::from(self.latitude).to_radians(),
+            f64::from(self.longitude).to_radians(),
+        )
+    }
+    /// Coordinates rounded to 5 decimals
+    fn coordinates_key(&self) -> (i64, i64) {
+        let quantize = |degrees: f32| (f64::from(degrees) * 1e5).round() as i64;
+        (quantize(self.latitude), quantize(self.longitude))
+    }
+    /// Rounded coordinates, plus the offset in seconds and the elevation in centimeters
+    fn key(&self) -> ((i64, i64), Option<i64>, i64) {
+        let quantize = |value: f32, factor: f64| (f64::from(value) * factor).round() as i64;
+        (
+            self.coordinates_key(),
+            self.timezone.map(|hours| quantize(hours, 3600.0)),
+            quantize(self.elevation_meters, 100.0),
+        )
+    }
+}
+
+/// Round `value` to `decimals` places
+fn round(value: f32, decimals: u32) -> f32 {
+    let factor = 10_f64.powi(decimals as i32);
+    ((f64::from(value) * factor).round() / factor) as f32
+}
+
+/// Near-identical locations are equal, so they can share a cache entry
+impl PartialEq for Location {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Location {}
+
+impl Hash for Location {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+/// The wall clock at a location, read from UTC and the location's own
+/// offset and daylight saving rather than the machine's time zone
+#[derive(Debug, Copy, Clone)]
+pub struct LocationClock {
+    location: Location,
+    config: Config,
+}
+
+impl LocationClock {
+    pub const fn new(location: Location, config: Config) -> Self {
+        Self { location, config }
+    }
+    /// Wall-clock time at the location at the given UTC time
+    pub fn at(&self, utc: DateTime) -> DateTime {
+        // both offsets are looked up twelve hours into the date they are given
+        let date = utc - Duration::hours(12);
+        let hours = PrayerTimes::utc_offset(date, self.location, self.config)
+            + PrayerTimes::summer_hours(date, self.config);
+        utc + Duration::seconds((hours * 3600.0).round() as i64)
+    }
+}
+
+impl Clock for LocationClock {
+    fn now(&self) -> DateTime {
+        self.at(chrono::Utc::now().naive_utc())
     }
 }
 
 #[derive(Debug, Copy, Clone)]
 pub struct PrayerSchedule {
     location: Location,
-    date: Date,
+    /// today at the location, unless set with `on`
+    date: Option<Date>,
     config: Config,
 }
 
@@ -36,13 +234,17 @@ impl PrayerSchedule {
     pub fn new(location: Location) -> Result<Self, crate::Error> {
         Ok(Self {
             location,
-            date: time::today(),
+            date: None,
             // default config
             config: Config::new(),
         })
     }
+    /// Like `new`, but starting on the date of the given clock
+    pub fn new_with(location: Location, clock: &impl Clock) -> Result<Self, crate::Error> {
+        Ok(Self::new(location)?.on(clock.today()))
+    }
     pub const fn on(mut self, date: Date) -> Self {
-        self.date = date;
+        self.date = Some(date);
         self
     }
     pub const fn with_config(mut self, config: Config) -> Self {
@@ -50,63 +252,447 @@ impl PrayerSchedule {
         self
     }
     pub fn calculate(&self) -> Result<PrayerTimes, crate::Error> {
-        PrayerTimes::new(self.date, self.location, self.config)
+        let date = self
+            .date
+            .unwrap_or_else(|| LocationClock::new(self.location, self.config).today());
+        PrayerTimes::new(date, self.location, self.config)
+    }
+    /// Earliest and latest time of day `prayer` falls on during `year`
+    pub fn extremes(
+        &self,
+        year: i32,
+        prayer: Prayer,
+    ) -> Result<(DateTime, DateTime), crate::Error> {
+        let mut date = time::date(year, 1, 1)?;
+        let mut extremes: Option<(DateTime, DateTime)> = None;
+        while date.year() == year {
+            let time = PrayerTimes::new(date, self.location, self.config)?.time(prayer);
+            extremes = Some(match extremes {
+                Some((earliest, latest)) => (
+                    if time.time() < earliest.time() {
+                        time
+                    } else {
+                        earliest
+                    },
+                    if time.time() > latest.time() {
+                        time
+                    } else {
+                        latest
+                    },
+                ),
+                None => (time, time),
+            });
+            date = date.succ_opt().ok_or(crate::Error::InvalidTime)?;
+        }
+        extremes.ok_or(crate::Error::InvalidTime)
+    }
+    /// Times for every day from `start` to `end`, both included
+    pub fn range(
+        &self,
+        start: Date,
+        end: Date,
+    ) -> impl Iterator<Item = Result<PrayerTimes, crate::Error>> {
+        let (location, config) = (self.location, self.config);
+        std::iter::successors(Some(start), |date| date.succ_opt())
+            .take_while(move |&date| date <= end)
+            .map(move |date| PrayerTimes::new(date, location, config))
+    }
+    /// Timetable of a whole month as CSV, one row per day after a header row
+    pub fn month_csv(&self, year: i32, month: u32) -> Result<String, crate::Error> {
+        let start = time::date(year, month, 1)?;
+        let end = if month == 12 {
+            time::date(year + 1, 1, 1)?
+        } else {
+            time::date(year, month + 1, 1)?
+        }
+        .pred_opt()
+        .ok_or(crate::Error::InvalidTime)?;
+
+        let mut csv = format!("{CSV_HEADER}\n");
+        for times in self.range(start, end) {
+            csv.push_str(&times?.to_csv_row());
+            csv.push('\n');
+        }
+        Ok(csv)
+    }
+    /// Times for each of `dates`, in the given order
+    pub fn on_dates(&self, dates: &[Date]) -> Result<Vec<PrayerTimes>, crate::Error> {
+        dates
+            .iter()
+            .map(|&date| PrayerTimes::new(date, self.location, self.config))
+            .collect()
     }
 }
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(try_from = "rfc3339::PrayerTimes")
+)]
 pub struct PrayerTimes {
     pub date: DateTime,
     pub location: Location,
     pub config: Config,
     pub dohr: DateTime,
     pub asr: DateTime,
+    /// asr with one more shadow length than the configured madhab
+    pub asr_secondary: DateTime,
+    /// Hanafi asr, computed alongside the configured madhab with `with_both_asr`
+    pub asr_hanafi: Option<DateTime>,
     pub maghreb: DateTime,
     pub ishaa: DateTime,
     pub fajr: DateTime,
+    /// end of the suhoor, `imsak_minutes` before Fajr
+    pub imsak: DateTime,
     pub fajr_tomorrow: DateTime,
+    pub sherook_tomorrow: DateTime,
     pub sherook: DateTime,
+    /// prayer shortly after sunrise, `ishraq_minutes` after it
+    pub ishraq: DateTime,
+    /// forenoon prayer, `duha_minutes` after sunrise
+    pub duha: DateTime,
+    pub first_third_of_night: DateTime,
+    pub midnight: DateTime,
+    pub last_third_of_night: DateTime,
+    /// solar positions of the date and the next, reused by `recompute_night`
+    sun: Sun,
+    sun_tomorrow: Sun,
+}
+
+/// One location of `compute_grid`, with its own result
+pub type GridCell = (Location, Result<PrayerTimes, crate::Error>);
+
+/// Compute the prayer times of a date over a grid of coordinates,
+/// e.g. to render a map. Both ranges are walked by `step` degrees.
+/// Each cell has its own result, so polar cells don't fail the whole grid.
+pub fn compute_grid(
+    latitudes: RangeInclusive<f32>,
+    longitudes: RangeInclusive<f32>,
+    step: f32,
+    date: Date,
+    config: Config,
+) -> Result<Vec<GridCell>, crate::Error> {
+    if step <= 0.0 || !step.is_finite() {
+        return Err(crate::Error::InvalidArgument(format!(
+            "Grid step must be positive, got {step}"
+        )));
+    }
+    let axis = |range: &RangeInclusive<f32>| {
+        let (start, end) = (*range.start(), *range.end());
+        (0..)
+            .map(move |i| (i as f32).mul_add(step, start))
+            .take_while(move |&value| value <= end)
+    };
+    let date = date.and_hms_opt(0, 0, 0).ok_or(crate::Error::InvalidTime)?;
+    let sun = Sun::new(date, config)?;
+    let sun_tomorrow = Sun::new(date + Duration::days(1), config)?;
+    Ok(axis(&latitudes)
+        .flat_map(|latitude| {
+            axis(&longitudes).map(move |longitude| Location::new(latitude, longitude))
+        })
+        .map(|location| {
+            let times = PrayerTimes::with_sun(date, location, config, sun, sun_tomorrow);
+            (location, times)
+        })
+        .collect())
+}
+
+/// Recommended times, apart from the obligatory prayers
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct SunnahTimes {
+    pub imsak: DateTime,
+    pub ishraq: DateTime,
+    pub duha: DateTime,
     pub first_third_of_night: DateTime,
     pub midnight: DateTime,
     pub last_third_of_night: DateTime,
+    /// Qiyam, from the configured Tahajjud start until Fajr
+    pub tahajjud: (DateTime, DateTime),
+}
+
+/// Advisory about computed times that are valid but unusual
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Warning {
+    /// beyond 48.5°, where twilight may last all night in summer
+    HighLatitude,
+    /// Ishaa starts less than half an hour before the middle of the night, or after it
+    IshaaNearMidnight,
+    /// the window of this prayer is under 20 minutes
+    ShortWindow(Prayer),
+}
+
+/// General twilight, by how far the sun is below the horizon
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum TwilightKind {
+    /// 6°
+    Civil,
+    /// 12°
+    Nautical,
+    /// 18°
+    Astronomical,
+}
+
+impl TwilightKind {
+    /// Depression of the sun below the horizon, in degrees
+    pub const fn depression(self) -> f32 {
+        match self {
+            Self::Civil => 6.0,
+            Self::Nautical => 12.0,
+            Self::Astronomical => 18.0,
+        }
+    }
+}
+
+/// The upcoming prayer
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct Countdown {
+    pub prayer: Prayer,
+    /// when the prayer starts
+    pub at: DateTime,
+    /// time left until it starts
+    pub remaining: Duration,
+}
+
+/// `Asr in 01:02:03`
+impl fmt::Display for Countdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.remaining.num_seconds().max(0);
+        write!(
+            f,
+            "{} in {:02}:{:02}:{:02}",
+            self.prayer,
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    }
+}
+
+/// Date-only solar quantities, shared by every location on that date
+#[derive(Debug, Copy, Clone)]
+struct Sun {
+    /// equation of time, in minutes
+    equation_of_time: f64,
+    /// sun declination, in degrees
+    declination: f64,
+}
+
+impl Sun {
+    fn new(date: DateTime, config: Config) -> Result<Self, crate::Error> {
+        let julian_day = cal::gregorian_to_julian(date.date());
+        Ok(Self {
+            equation_of_time: cal::equation_of_time(julian_day.into()),
+            declination: match config.declination_override {
+                Some(declination) => declination.into(),
+                None => PrayerTimes::sun_declination(date, config)?,
+            },
+        })
+    }
 }
 
 impl PrayerTimes {
     pub fn new(date: Date, location: Location, config: Config) -> Result<Self, crate::Error> {
         let date = date.and_hms_opt(0, 0, 0).ok_or(crate::Error::InvalidTime)?;
+        let sun = Sun::new(date, config)?;
+        let sun_tomorrow = Sun::new(date + Duration::days(1), config)?;
+        Self::with_sun(date, location, config, sun, sun_tomorrow)
+    }
+    /// Shorthand for `new(..).ok()`, for callers that only care whether every time
+    /// is defined, e.g. not when the sun never sets or never reaches the Fajr angle
+    pub fn checked(date: Date, location: Location, config: Config) -> Option<Self> {
+        Self::new(date, location, config).ok()
+    }
+    /// Like `new`, but with a fixed sun declination (in degrees)
+    /// instead of the astronomical one, e.g. to reproduce a reference table.
+    /// It is kept in `config`, so the derived methods use it too.
+    pub fn with_declination_override(
+        date: Date,
+        location: Location,
+        config: Config,
+        declination: f32,
+    ) -> Result<Self, crate::Error> {
+        let config = Config {
+            declination_override: Some(declination),
+            ..config
+        };
+        Self::new(date, location, config)
+    }
+    /// Compute the times of a single date for many locations.
+    /// The solar position only depends on the date, so it is computed once.
+    pub fn for_locations(
+        date: Date,
+        locations: &[Location],
+        config: Config,
+    ) -> Result<Vec<Self>, crate::Error> {
+        let date = date.and_hms_opt(0, 0, 0).ok_or(crate::Error::InvalidTime)?;
+        let sun = Sun::new(date, config)?;
+        let sun_tomorrow = Sun::new(date + Duration::days(1), config)?;
+        locations
+            .iter()
+            .map(|&location| Self::with_sun(date, location, config, sun, sun_tomorrow))
+            .collect()
+    }
+    /// Compute the times of the local date at the given Unix time
+    pub fn at_epoch(secs: i64, location: Location, config: Config) -> Result<Self, crate::Error> {
+        let utc = time::from_unix(secs).ok_or(crate::Error::InvalidTime)?;
+        let local = LocationClock::new(location, config).at(utc);
+        Self::new(local.date(), location, config)
+    }
+    /// Compute the times along with advisories about unusual results
+    pub fn new_with_warnings(
+        date: Date,
+        location: Location,
+        config: Config,
+    ) -> Result<(Self, Vec<Warning>), crate::Error> {
+        let prayer_times = Self::new(date, location, config)?;
+        let mut warnings = Vec::new();
+        if location.latitude.abs() > 48.5 {
+            warnings.push(Warning::HighLatitude);
+        }
+        let midnight = prayer_times.night_time(prayer_times.midnight);
+        if midnight - prayer_times.night_time(prayer_times.ishaa) < Duration::minutes(30) {
+            warnings.push(Warning::IshaaNearMidnight);
+        }
+        for prayer in PRAYERS {
+            let window = prayer_times.window(prayer);
+            if window.end - window.start < Duration::minutes(20) {
+                warnings.push(Warning::ShortWindow(prayer));
+            }
+        }
+        Ok((prayer_times, warnings))
+    }
+    /// Compute the times of the date `days_since_1970` days after the Unix epoch
+    pub fn from_day_number(
+        days_since_1970: i64,
+        location: Location,
+        config: Config,
+    ) -> Result<Self, crate::Error> {
+        let date = days_since_1970
+            .checked_mul(86_400)
+            .and_then(time::from_unix)
+            .ok_or(crate::Error::InvalidTime)?;
+        Self::new(date.date(), location, config)
+    }
+    fn with_sun(
+        date: DateTime,
+        location: Location,
+        config: Config,
+        sun: Sun,
+        sun_tomorrow: Sun,
+    ) -> Result<Self, crate::Error> {
+        config.validate()?;
+        // `time_for_angle` divides by the cosine of the latitude
+        if location.latitude.abs() >= MAX_LATITUDE {
+            return Err(crate::Error::PolarLocation);
+        }
 
         // dohr time must be calculated at first, every other time depends on it!
-        let dohr_time = Self::dohr(date, location)?;
-        let dohr = Self::hours_to_time(date, dohr_time, 0.0, config)?;
+        let dohr_time = Self::dohr(date, sun, location, config)?;
+        let dohr = Self::hours_to_time(
+            date,
+            dohr_time,
+            config.adjustments.seconds(Prayer::Dohr),
+            config,
+        )?;
+
+        let shadow = Self::shadow(config);
+        let asr_time = Self::asr(dohr_time, shadow, sun, location, config)?;
+        let asr = Self::hours_to_time(
+            date,
+            asr_time,
+            config.adjustments.seconds(Prayer::Asr),
+            config,
+        )?;
+
+        let asr_secondary_time = Self::asr(dohr_time, shadow + 1.0, sun, location, config)?;
+        let asr_secondary = Self::hours_to_time(
+            date,
+            asr_secondary_time,
+            config.adjustments.seconds(Prayer::Asr),
+            config,
+        )?;
 
-        let asr_time = Self::asr(date, location, config)?;
-        let asr = Self::hours_to_time(date, asr_time, 0.0, config)?;
+        let asr_hanafi = if config.both_asr {
+            let shadow = Madhab::Hanafi.shadow_factor().into();
+            let asr_hanafi_time = Self::asr(dohr_time, shadow, sun, location, config)?;
+            Some(Self::hours_to_time(
+                date,
+                asr_hanafi_time,
+                config.adjustments.seconds(Prayer::Asr),
+                config,
+            )?)
+        } else {
+            None
+        };
 
-        let maghreb_time = Self::maghreb(date, location, config)?;
-        let maghreb = Self::hours_to_time(date, maghreb_time, 0.0, config)?;
+        let maghreb_time = Self::maghreb(dohr_time, sun, location, config)?;
+        let maghreb = Self::hours_to_time(
+            date,
+            maghreb_time,
+            config.adjustments.seconds(Prayer::Maghreb),
+            config,
+        )?;
 
-        let ishaa_time = Self::ishaa(date, location, config)?;
-        let ishaa = Self::hours_to_time(date, ishaa_time, 0.0, config)?;
+        let ishaa_time = Self::ishaa(dohr_time, date, sun, location, config)?;
+        let ishaa = Self::hours_to_time(
+            date,
+            ishaa_time,
+            config.adjustments.seconds(Prayer::Ishaa),
+            config,
+        )?;
 
-        let fajr_time = Self::fajr(date, location, config)?;
-        let fajr = Self::hours_to_time(date, fajr_time, 0.0, config)?;
+        let fajr_time = Self::fajr(dohr_time, sun, location, config)?;
+        let fajr = Self::hours_to_time(
+            date,
+            fajr_time,
+            config.adjustments.seconds(Prayer::Fajr),
+            config,
+        )?;
+        let imsak = fajr - Duration::minutes(config.imsak_minutes.into());
 
-        let sherook_time = Self::sherook(date, location, config)?;
-        let sherook = Self::hours_to_time(date, sherook_time, 0.0, config)?;
+        let sherook_time = Self::sherook(dohr_time, sun, location, config)?;
+        let sherook = Self::hours_to_time(
+            date,
+            sherook_time,
+            config.adjustments.seconds(Prayer::Sherook),
+            config,
+        )?;
+        let ishraq = sherook + Duration::minutes(config.ishraq_minutes.into());
+        let duha = sherook + Duration::minutes(config.duha_minutes.into());
 
-        // These must be called after ishaa, since they depends on it
-        let first_third_of_night_time = Self::first_third_of_night(date, location, config)?;
+        // These must be called after maghreb and fajr, since they depends on them
+        let first_third_of_night_time = Self::first_third_of_night(maghreb_time, fajr_time);
         let first_third_of_night =
             Self::hours_to_time(date, first_third_of_night_time, 0.0, config)?;
-        let midnight_time = Self::midnight(date, location, config)?;
+        let midnight_time = Self::midnight(maghreb_time, fajr_time);
         let midnight = Self::hours_to_time(date, midnight_time, 0.0, config)?;
 
-        let last_third_of_night_time = Self::last_third_of_night(date, location, config)?;
+        let last_third_of_night_time = Self::last_third_of_night(maghreb_time, fajr_time);
         let last_third_of_night = Self::hours_to_time(date, last_third_of_night_time, 0.0, config)?;
 
         let tomorrow = date + Duration::days(1);
-        let fajr_time_tomorrow = Self::fajr(tomorrow, location, config)?;
-        let fajr_tomorrow = Self::hours_to_time(tomorrow, fajr_time_tomorrow, 0.0, config)?;
+        let dohr_time_tomorrow = Self::dohr(tomorrow, sun_tomorrow, location, config)?;
+        let fajr_time_tomorrow = Self::fajr(dohr_time_tomorrow, sun_tomorrow, location, config)?;
+        let fajr_tomorrow = Self::hours_to_time(
+            tomorrow,
+            fajr_time_tomorrow,
+            config.adjustments.seconds(Prayer::Fajr),
+            config,
+        )?;
+        let sherook_time_tomorrow =
+            Self::sherook(dohr_time_tomorrow, sun_tomorrow, location, config)?;
+        let sherook_tomorrow = Self::hours_to_time(
+            tomorrow,
+            sherook_time_tomorrow,
+            config.adjustments.seconds(Prayer::Sherook),
+            config,
+        )?;
+
+        let ordered = [fajr, sherook, dohr, asr, maghreb, ishaa];
+        if config.strict_ordering && ordered.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(crate::Error::NonMonotonicTimes);
+        }
 
         Ok(Self {
             date,
@@ -114,52 +700,126 @@ impl PrayerTimes {
             config,
             dohr,
             asr,
+            asr_secondary,
+            asr_hanafi,
             maghreb,
             ishaa,
             fajr,
+            imsak,
             fajr_tomorrow,
+            sherook_tomorrow,
             sherook,
+            ishraq,
+            duha,
             first_third_of_night,
             midnight,
             last_third_of_night,
+            sun,
+            sun_tomorrow,
         })
     }
-    /// Get the Dohr
-    fn dohr(date: DateTime, location: Location) -> Result<f32, crate::Error> {
-        let longitude_difference = Self::longitude_difference(location)?;
+    /// Recompute Fajr and the night times from `config`, after changing the Fajr angle.
+    /// The other prayers are left untouched, and the solar positions computed
+    /// with the times are reused, so a new obliquity or declination needs `new`.
+    pub fn recompute_night(&mut self) -> Result<(), crate::Error> {
+        let (date, location, config) = (self.date, self.location, self.config);
+        let (sun, sun_tomorrow) = (self.sun, self.sun_tomorrow);
+        config.validate()?;
+        let dohr_time = Self::dohr(date, sun, location, config)?;
+        let maghreb_time = Self::maghreb(dohr_time, sun, location, config)?;
+        let fajr_time = Self::fajr(dohr_time, sun, location, config)?;
+        self.fajr = Self::hours_to_time(
+            date,
+            fajr_time,
+            config.adjustments.seconds(Prayer::Fajr),
+            config,
+        )?;
+        self.imsak = self.fajr - Duration::minutes(config.imsak_minutes.into());
 
-        let julian_day = cal::gregorian_to_julian(date.date());
-        let time_equation = cal::equation_of_time(julian_day);
-        Ok((12.0 + longitude_difference) + (time_equation / 60.0))
+        let first_third_of_night_time = Self::first_third_of_night(maghreb_time, fajr_time);
+        self.first_third_of_night =
+            Self::hours_to_time(date, first_third_of_night_time, 0.0, config)?;
+        let midnight_time = Self::midnight(maghreb_time, fajr_time);
+        self.midnight = Self::hours_to_time(date, midnight_time, 0.0, config)?;
+        let last_third_of_night_time = Self::last_third_of_night(maghreb_time, fajr_time);
+        self.last_third_of_night =
+            Self::hours_to_time(date, last_third_of_night_time, 0.0, config)?;
+
+        let tomorrow = date + Duration::days(1);
+        let dohr_time_tomorrow = Self::dohr(tomorrow, sun_tomorrow, location, config)?;
+        let fajr_time_tomorrow = Self::fajr(dohr_time_tomorrow, sun_tomorrow, location, config)?;
+        self.fajr_tomorrow = Self::hours_to_time(
+            tomorrow,
+            fajr_time_tomorrow,
+            config.adjustments.seconds(Prayer::Fajr),
+            config,
+        )?;
+        Ok(())
+    }
+    /// Get the Dohr
+    fn dohr(
+        date: DateTime,
+        sun: Sun,
+        location: Location,
+        config: Config,
+    ) -> Result<f32, crate::Error> {
+        let longitude_difference = Self::longitude_difference(date, location, config)?;
+        Ok((12.0 + longitude_difference) + (sun.equation_of_time / 60.0) as f32)
     }
     /// Get the Asr time
-    fn asr(date: DateTime, location: Location, config: Config) -> Result<f32, crate::Error> {
-        let dohr_time = Self::dohr(date, location)?;
-        let angle = Self::asr_angle(date, location, config)?;
-        Ok(dohr_time + Self::time_for_angle(angle, date, location)?)
+    fn asr(
+        dohr_time: f32,
+        shadow: f32,
+        sun: Sun,
+        location: Location,
+        config: Config,
+    ) -> Result<f32, crate::Error> {
+        let angle = Self::asr_angle(shadow, sun, location, config)?;
+        let offset = Self::time_for_angle(angle, sun, location);
+        Ok(dohr_time
+            + offset.ok_or(crate::Error::NoValidTime {
+                prayer: Prayer::Asr,
+            })?)
     }
     /// Get the Maghreb time
-    fn maghreb(date: DateTime, location: Location, _config: Config) -> Result<f32, crate::Error> {
-        let dohr_time = Self::dohr(date, location)?;
-
-        let angle = 90.83333; // constants
-        Ok(dohr_time + Self::time_for_angle(angle, date, location)?)
+    fn maghreb(
+        dohr_time: f32,
+        sun: Sun,
+        location: Location,
+        config: Config,
+    ) -> Result<f32, crate::Error> {
+        let offset = Self::time_for_angle(Self::maghreb_angle(location, config), sun, location);
+        Ok(dohr_time
+            + offset.ok_or(crate::Error::NoValidTime {
+                prayer: Prayer::Maghreb,
+            })?)
+    }
+    /// Zenith angle of Maghreb, the configured depression or else the
+    /// sunset angle at the location's elevation
+    fn maghreb_angle(location: Location, config: Config) -> f32 {
+        config
+            .maghreb_angle
+            .map_or(location.sunset_angle(), |depression| 90.0 + depression)
     }
     /// Get the Ishaa time
-    fn ishaa(date: DateTime, location: Location, config: Config) -> Result<f32, crate::Error> {
-        let dohr_time = Self::dohr(date, location)?;
-
+    fn ishaa(
+        dohr_time: f32,
+        date: DateTime,
+        sun: Sun,
+        location: Location,
+        config: Config,
+    ) -> Result<f32, crate::Error> {
         // checking one of `all_year` or `ramadan` is enough
         // because if set, none of them would be 0.0
-        if config.isha_interval.all_year > 0.0 {
-            let is_ramadan = HijriDate::from_gregorian(date.date(), 0).month == 9;
+        if config.isha_interval.all_year > 0.0 && !config.prefer_angle_isha {
+            let is_ramadan =
+                HijriDate::from_gregorian(date.date(), config.hijri_adjustment).month == 9;
             let time_after_maghreb = if is_ramadan {
                 config.isha_interval.ramdan / 60.0
             } else {
                 config.isha_interval.all_year / 60.0
             };
-            let angle = 90.83333; //  Constants (maghreb angle)
-            Ok(time_after_maghreb + dohr_time + Self::time_for_angle(angle, date, location)?)
+            Ok(time_after_maghreb + Self::maghreb(dohr_time, sun, location, config)?)
         } else {
             // NOTE (upstream) why still need FixedInterval comparison?
             // let angle = if config.method == Method::FixedInterval {
@@ -168,12 +828,20 @@ impl PrayerTimes {
             //     config.ishaa_angle + 90.0
             // };
             let angle = config.ishaa_angle + 90.0;
-            Ok(dohr_time + Self::time_for_angle(angle, date, location)?)
+            let offset = Self::twilight_offset(angle, sun, location, config);
+            Ok(dohr_time
+                + offset.ok_or(crate::Error::NoValidTime {
+                    prayer: Prayer::Ishaa,
+                })?)
         }
     }
     /// Get the Fajr time
-    fn fajr(date: DateTime, location: Location, config: Config) -> Result<f32, crate::Error> {
-        let dohr_time = Self::dohr(date, location)?;
+    fn fajr(
+        dohr_time: f32,
+        sun: Sun,
+        location: Location,
+        config: Config,
+    ) -> Result<f32, crate::Error> {
         // NOTE (upstream) wrong if-else?
         // let angle = if config.method == Method::FixedInterval {
         //     config.fajr_angle + 90.0
@@ -181,42 +849,50 @@ impl PrayerTimes {
         //     config.fajr_angle
         // };
         let angle = config.fajr_angle + 90.0;
-        Ok(dohr_time - Self::time_for_angle(angle, date, location)?)
+        let offset = Self::twilight_offset(angle, sun, location, config);
+        Ok(dohr_time
+            - offset.ok_or(crate::Error::NoValidTime {
+                prayer: Prayer::Fajr,
+            })?)
     }
-    /// Get the Sherook time
-    fn sherook(date: DateTime, location: Location, _config: Config) -> Result<f32, crate::Error> {
-        let dohr_time = Self::dohr(date, location)?;
-
-        let angle = 90.83333;
-        Ok(dohr_time - Self::time_for_angle(angle, date, location)?)
+    /// Hours between Dohr and the sun reaching `angle`, falling back to the
+    /// high latitude rule when it never does
+    fn twilight_offset(angle: f32, sun: Sun, location: Location, config: Config) -> Option<f32> {
+        let offset = Self::time_for_angle(angle, sun, location);
+        match config.high_latitude_rule {
+            Some(rule) if offset.is_none() => {
+                let sunset = Self::time_for_angle(location.sunset_angle(), sun, location)?;
+                let night = 24.0 - 2.0 * sunset;
+                Some(sunset + rule.portion(angle - 90.0) * night)
+            }
+            _ => offset,
+        }
     }
-    /// Get the third of night
-    fn first_third_of_night(
-        date: DateTime,
+    /// Get the Sherook time
+    fn sherook(
+        dohr_time: f32,
+        sun: Sun,
         location: Location,
-        config: Config,
+        _config: Config,
     ) -> Result<f32, crate::Error> {
-        let maghreb_time = Self::maghreb(date, location, config)?;
-        let fajr_time = Self::fajr(date, location, config)?;
-        Ok(maghreb_time + (24.0 - (maghreb_time - fajr_time)) / 3.0)
+        let offset = Self::time_for_angle(location.sunset_angle(), sun, location);
+        Ok(dohr_time
+            - offset.ok_or(crate::Error::NoValidTime {
+                prayer: Prayer::Sherook,
+            })?)
+    }
+    /// Get the third of night
+    fn first_third_of_night(maghreb_time: f32, fajr_time: f32) -> f32 {
+        maghreb_time + (24.0 - (maghreb_time - fajr_time)) / 3.0
     }
     /// Midnight is the exact time between sunrise (Shorook) and sunset (Maghreb),
     /// It defines usually the end of Ishaa time
-    fn midnight(date: DateTime, location: Location, config: Config) -> Result<f32, crate::Error> {
-        let maghreb_time = Self::maghreb(date, location, config)?;
-        let fajr_time = Self::fajr(date, location, config)?;
-        Ok(maghreb_time + (24.0 - (maghreb_time - fajr_time)) / 2.0)
+    fn midnight(maghreb_time: f32, fajr_time: f32) -> f32 {
+        maghreb_time + (24.0 - (maghreb_time - fajr_time)) / 2.0
     }
     /// Qiyam time starts after Ishaa directly, however, the best time for Qiyam is the last third of night
-    fn last_third_of_night(
-        date: DateTime,
-        location: Location,
-        config: Config,
-    ) -> Result<f32, crate::Error> {
-        let maghreb_time = Self::maghreb(date, location, config)?;
-
-        let fajr_time = Self::fajr(date, location, config)?;
-        Ok(maghreb_time + (2.0 * (24.0 - (maghreb_time - fajr_time)) / 3.0))
+    fn last_third_of_night(maghreb_time: f32, fajr_time: f32) -> f32 {
+        maghreb_time + (2.0 * (24.0 - (maghreb_time - fajr_time)) / 3.0)
     }
     /// Convert a decimal value (in hours) to time object
     fn hours_to_time(
@@ -225,144 +901,738 @@ impl PrayerTimes {
         shift: f32,
         config: Config,
     ) -> Result<DateTime, crate::Error> {
-        let is_summer = i32::from(config.is_summer);
-        let hour = val + (shift / 3600.0);
+        let hour = val + (shift / 3600.0) + Self::summer_hours(date, config);
+        // casting NaN or infinity to an integer silently yields garbage
+        if !hour.is_finite() {
+            return Err(crate::Error::InvalidTime);
+        }
         let minute = (hour - (hour).floor()) * 60.0;
         let second = (minute - (minute).floor()) * 60.0;
-        let hour = (hour + is_summer as f32).floor() % 24.0;
+        // times before midnight or past the end of the day wrap around
+        let hour = (hour).floor().rem_euclid(24.0);
+        let whole = |value: f32| u32::try_from(value as i64).map_err(|_| crate::Error::InvalidTime);
         time::date(date.year(), date.month(), date.day())?
-            .and_hms_opt(hour as u32, minute as u32, second as u32)
+            .and_hms_opt(whole(hour)?, whole(minute)?, whole(second)?)
             .ok_or(crate::Error::InvalidTime)
     }
-    fn longitude_difference(location: Location) -> Result<f32, crate::Error> {
-        let offset_second = Local::now().offset().local_minus_utc();
-        let offset_hour = Duration::seconds(offset_second.into()).num_hours();
-        let middle_longitude = offset_hour as f32 * 15.0;
+    fn longitude_difference(
+        date: DateTime,
+        location: Location,
+        config: Config,
+    ) -> Result<f32, crate::Error> {
+        let middle_longitude = Self::utc_offset(date, location, config) * 15.0;
         Ok((middle_longitude - location.longitude) / 15.0)
     }
-    /// Get the angle angle for asr (according to choosen madhab)
-    fn asr_angle(date: DateTime, location: Location, config: Config) -> Result<f32, crate::Error> {
-        let delta = Self::sun_declination(date)?;
-        let x = cal::dsin(location.latitude).mul_add(
-            cal::dsin(delta),
-            cal::dcos(location.latitude) * cal::dcos(delta),
-        );
-        let a = (x / (-x).mul_add(x, 1.0).sqrt()).atan();
-        let x = config.madhab as i32 as f32 + (1.0 / (a).tan());
-        Ok(90.0 - (180.0 / PI) * 2.0_f32.mul_add((1.0_f32).atan(), (x).atan()))
+    /// Standard UTC offset of the location on `date`, in hours, never the
+    /// machine's own offset. Daylight saving is left to `summer_hours`.
+    #[cfg_attr(not(feature = "tz"), allow(unused_variables))]
+    fn utc_offset(date: DateTime, location: Location, config: Config) -> f32 {
+        #[cfg(feature = "tz")]
+        if let Some(zone) = config.zone {
+            use chrono::TimeZone;
+            use chrono_tz::OffsetComponents;
+
+            let offset = zone.offset_from_utc_datetime(&(date + Duration::hours(12)));
+            return offset.base_utc_offset().num_minutes() as f32 / 60.0;
+        }
+        // an explicit offset on the location wins over the config's
+        location
+            .timezone
+            .or(config.timezone)
+            .unwrap_or_else(|| location.timezone())
+    }
+    /// Daylight saving shift on `date`, in hours. A configured zone knows the
+    /// shift of each date, otherwise `is_summer` applies to all of them.
+    #[cfg_attr(not(feature = "tz"), allow(unused_variables))]
+    fn summer_hours(date: DateTime, config: Config) -> f32 {
+        #[cfg(feature = "tz")]
+        if let Some(zone) = config.zone {
+            use chrono::TimeZone;
+            use chrono_tz::OffsetComponents;
+
+            let offset = zone.offset_from_utc_datetime(&(date + Duration::hours(12)));
+            return offset.dst_offset().num_minutes() as f32 / 60.0;
+        }
+        f32::from(u8::from(config.is_summer))
+    }
+    /// Shadow factor for asr, the configured override or the madhab's
+    fn shadow(config: Config) -> f32 {
+        config
+            .shadow_factor
+            .unwrap_or_else(|| config.madhab.shadow_factor())
+            .into()
+    }
+    /// Get the angle angle for asr, when the shadow is `shadow` times the object length
+    fn asr_angle(
+        shadow: f32,
+        sun: Sun,
+        location: Location,
+        config: Config,
+    ) -> Result<f64, crate::Error> {
+        let (delta, latitude) = (sun.declination, f64::from(location.latitude));
+        let x =
+            cal::dsin(latitude).mul_add(cal::dsin(delta), cal::dcos(latitude) * cal::dcos(delta));
+        // `a` is the sun's altitude at noon. Clamp rounding errors and keep the
+        // cotangent finite when the sun passes close to the zenith or horizon.
+        let a = x.clamp(-1.0, 1.0).asin();
+        let x = f64::from(shadow) + a.cos() / a.sin().max(f64::EPSILON);
+        let angle = 90.0 - (180.0 / PI) * 2.0_f64.mul_add((1.0_f64).atan(), (x).atan());
+        // `angle` is the negated zenith distance of the sun's center.
+        // Putting the lower limb there brings the center closer to the zenith.
+        if config.asr_lower_limb {
+            Ok(angle + f64::from(SUN_SEMIDIAMETER))
+        } else {
+            Ok(angle)
+        }
     }
-    /// Get Times for "Fajr, Sherook, Asr, Maghreb, ishaa"
-    fn time_for_angle(angle: f32, date: DateTime, location: Location) -> Result<f32, crate::Error> {
-        let delta = Self::sun_declination(date)?;
-        let s = (cal::dcos(angle) - cal::dsin(location.latitude) * cal::dsin(delta))
-            / (cal::dcos(location.latitude) * cal::dcos(delta));
-        Ok((180.0 / PI * ((-s / (-s).mul_add(s, 1.0).sqrt()).atan() + PI / 2.0)) / 15.0)
+    /// Get Times for "Fajr, Sherook, Asr, Maghreb, ishaa",
+    /// or `None` when the sun never reaches `angle` on that day
+    fn time_for_angle(angle: impl Into<f64>, sun: Sun, location: Location) -> Option<f32> {
+        let (delta, latitude) = (sun.declination, f64::from(location.latitude));
+        let s = (cal::dcos(angle.into()) - cal::dsin(latitude) * cal::dsin(delta))
+            / (cal::dcos(latitude) * cal::dcos(delta));
+        // the sun only just touching `angle`, as at noon, can round past 1
+        if s.abs() > 1.0 + 1e-12 {
+            return None;
+        }
+        let s = s.clamp(-1.0, 1.0);
+        Some(((180.0 / PI * ((-s / (-s).mul_add(s, 1.0).sqrt()).atan() + PI / 2.0)) / 15.0) as f32)
     }
     /// Get sun declination
-    fn sun_declination(date: DateTime) -> Result<f32, crate::Error> {
-        let julian_day = cal::gregorian_to_julian(date.date());
+    fn sun_declination(date: DateTime, config: Config) -> Result<f64, crate::Error> {
+        let julian_day = f64::from(cal::gregorian_to_julian(date.date()));
         let n = julian_day - 2_451_544.5;
-        let epsilon = 23.44 - (0.000_000_4 * n);
-        let l = 0.985_647_4_f32.mul_add(n, 280.466);
-        let g = 0.985_600_3_f32.mul_add(n, 357.528);
-        let lamda = 0.02_f32.mul_add(cal::dsin(2.0 * g), 1.915_f32.mul_add(cal::dsin(g), l));
+        let epsilon = f64::from(config.obliquity) - (0.000_000_4 * n);
+        let l = 0.985_647_4_f64.mul_add(n, 280.466);
+        let g = 0.985_600_3_f64.mul_add(n, 357.528);
+        let lamda = 0.02_f64.mul_add(cal::dsin(2.0 * g), 1.915_f64.mul_add(cal::dsin(g), l));
         let x = cal::dsin(epsilon) * cal::dsin(lamda);
-        Ok((180.0 / (4.0 * (1.0_f32).atan())) * (x / (-x).mul_add(x, 1.0).sqrt()).atan())
+        Ok((180.0 / (4.0 * (1.0_f64).atan())) * (x / (-x).mul_add(x, 1.0).sqrt()).atan())
+    }
+    /// Preferred (delayed) asr, the configured offset after the earliest asr
+    pub fn asr_preferred(&self) -> DateTime {
+        self.asr + Duration::minutes(self.config.asr_preferred_offset.into())
+    }
+    /// Compass bearing of the sun at Sherook, in degrees from true north
+    pub fn sunrise_azimuth(&self) -> Result<f32, crate::Error> {
+        let sun = Sun::new(self.date, self.config)?;
+        let cos_azimuth = cal::dsin(sun.declination) / cal::dcos(self.location.latitude.into());
+        Ok(cos_azimuth.clamp(-1.0, 1.0).acos().to_degrees() as f32)
+    }
+    /// Compass bearing of the sun at Maghreb, in degrees from true north
+    pub fn sunset_azimuth(&self) -> Result<f32, crate::Error> {
+        Ok(360.0 - self.sunrise_azimuth()?)
+    }
+    /// The sun's hour angle at the given prayer, in degrees.
+    /// Negative before the solar transit (Dohr), positive after.
+    pub fn hour_angle(&self, prayer: Prayer) -> Result<f32, crate::Error> {
+        let (date, location, config) = (self.date, self.location, self.config);
+        let sun = Sun::new(date, config)?;
+        let dohr_time = Self::dohr(date, sun, location, config)?;
+        let time = match prayer {
+            Prayer::Fajr => Self::fajr(dohr_time, sun, location, config)?,
+            Prayer::Sherook => Self::sherook(dohr_time, sun, location, config)?,
+            Prayer::Dohr => dohr_time,
+            Prayer::Asr => {
+                let shadow = Self::shadow(config);
+                Self::asr(dohr_time, shadow, sun, location, config)?
+            }
+            Prayer::Maghreb => Self::maghreb(dohr_time, sun, location, config)?,
+            Prayer::Ishaa => Self::ishaa(dohr_time, date, sun, location, config)?,
+        };
+        // the sun moves 15° per hour
+        Ok((time - dohr_time) * 15.0)
+    }
+    /// Time between the geometric sunset and Maghreb,
+    /// which waits for the upper limb to disappear below the refracted horizon.
+    /// Follows the elevation and `maghreb_angle` Maghreb is computed with.
+    pub fn maghreb_refraction_delta(&self) -> Result<Duration, crate::Error> {
+        let sun = Sun::new(self.date, self.config)?;
+        let sunset = Self::time_for_angle(90.0, sun, self.location);
+        let angle = Self::maghreb_angle(self.location, self.config);
+        let maghreb = Self::time_for_angle(angle, sun, self.location);
+        let (sunset, maghreb) = sunset.zip(maghreb).ok_or(crate::Error::NoValidTime {
+            prayer: Prayer::Maghreb,
+        })?;
+        Ok(Duration::seconds(
+            ((maghreb - sunset) * 3600.0).round() as i64
+        ))
+    }
+    /// Beginning of the morning twilight or end of the evening one
+    pub fn twilight(&self, kind: TwilightKind, morning: bool) -> Result<DateTime, crate::Error> {
+        self.interpolate_depression(kind.depression(), morning)
+    }
+    /// When the sun is `depression` degrees below the horizon, in the morning or evening.
+    /// Fajr, angle-based Ishaa and the twilights are all special cases of it, though
+    /// neither the high latitude rule nor the adjustments are applied here.
+    /// Fails with `NoValidTime` when the sun never gets that low, blaming Fajr in the
+    /// morning and Ishaa in the evening.
+    pub fn interpolate_depression(
+        &self,
+        depression: f32,
+        morning: bool,
+    ) -> Result<DateTime, crate::Error> {
+        let sun = Sun::new(self.date, self.config)?;
+        let dohr_time = Self::dohr(self.date, sun, self.location, self.config)?;
+        let prayer = if morning { Prayer::Fajr } else { Prayer::Ishaa };
+        let offset = Self::time_for_angle(90.0 + depression, sun, self.location)
+            .ok_or(crate::Error::NoValidTime { prayer })?;
+        let time = if morning {
+            dohr_time - offset
+        } else {
+            dohr_time + offset
+        };
+        Self::hours_to_time(self.date, time, 0.0, self.config)
     }
     /// Remaining time to next prayer
     pub fn time_remaining(&self) -> Result<(u32, u32), crate::Error> {
-        let next_prayer_time = self.time(self.next()?);
-        let now_to_next;
-        // Check if the next prayer is Fajr (Because Fajr time is less than current time)
-        if next_prayer_time < time::now() {
-            let time_before_midnight = match time::one_sec_before_midnight() {
-                Some(before_midnight) => before_midnight - time::now(),
-                None => Duration::zero(),
-            };
-            let time_after_midnight = match time::midnight() {
-                Some(after_midnight) => next_prayer_time - after_midnight,
-                None => Duration::zero(),
-            };
-
-            now_to_next = time_before_midnight + time_after_midnight;
-        } else {
-            now_to_next = next_prayer_time - time::now();
-        }
-        let now_to_next = now_to_next.num_seconds() as f64;
-
-        let whole: f64 = now_to_next / 60.0 / 60.0;
-        let fract = whole.fract();
-        let hours = whole.trunc() as u32;
-        let minutes = (fract * 60.0).round() as u32;
+        self.time_remaining_with(&self.clock())
+    }
+    /// Remaining time to next prayer, measured from the given clock
+    pub fn time_remaining_with(&self, clock: &impl Clock) -> Result<(u32, u32), crate::Error> {
+        let remaining = self.countdown(clock.now())?.remaining;
+        // round to the nearest minute
+        let minutes = u32::try_from((remaining.num_seconds() + 30) / 60).unwrap_or(0);
 
-        Ok((hours, minutes))
+        Ok((minutes / 60, minutes % 60))
     }
-    /// Get next prayer
-    pub fn next(&self) -> Result<Prayer, crate::Error> {
-        match self.current()? {
-            Prayer::Fajr => Ok(Prayer::Sherook),
-            Prayer::Sherook => Ok(Prayer::Dohr),
-            Prayer::Dohr => Ok(Prayer::Asr),
-            Prayer::Asr => Ok(Prayer::Maghreb),
-            Prayer::Maghreb => Ok(Prayer::Ishaa),
-            Prayer::Ishaa => Ok(Prayer::Fajr),
-        }
+    /// Exact time left until the next prayer, tomorrow's Fajr after Ishaa
+    pub fn duration_to_next(&self, now: DateTime) -> Result<Duration, crate::Error> {
+        Ok(self.countdown(now)?.remaining)
     }
-    /// Get prayer's time
-    pub fn time(&self, prayer: Prayer) -> DateTime {
-        match prayer {
-            Prayer::Fajr => self.fajr,
-            Prayer::Sherook => self.sherook,
-            Prayer::Dohr => self.dohr,
-            Prayer::Asr => self.asr,
-            Prayer::Maghreb => self.maghreb,
-            Prayer::Ishaa => self.ishaa,
-        }
+    /// Remaining time to next prayer, in hours, minutes and seconds
+    pub fn time_remaining_hms(&self) -> Result<(u32, u32, u32), crate::Error> {
+        self.time_remaining_hms_with(&self.clock())
     }
-    /// Get current prayer
-    pub fn current(&self) -> Result<Prayer, crate::Error> {
-        Ok(self.current_time(time::now()))
+    /// Remaining time to next prayer in hours, minutes and seconds, measured from the given clock
+    pub fn time_remaining_hms_with(
+        &self,
+        clock: &impl Clock,
+    ) -> Result<(u32, u32, u32), crate::Error> {
+        self.remaining_hms(clock.now())
     }
-    /// Helper function for `current`
-    fn current_time(&self, time: DateTime) -> Prayer {
-        // dummy value. it will replaced below
-        // just to avoid using `Option` or `Err`
-        let mut current_prayer = Prayer::Dohr;
-
-        let ranges = vec![
-            // fajr, fajr_range
-            (Prayer::Fajr, self.fajr..self.sherook),
-            (Prayer::Sherook, self.sherook..self.dohr),
-            (Prayer::Dohr, self.dohr..self.asr),
-            (Prayer::Asr, self.asr..self.maghreb),
-            (Prayer::Maghreb, self.maghreb..self.ishaa),
-            (Prayer::Ishaa, self.ishaa..self.fajr_tomorrow),
-        ];
-        for (prayer, range) in ranges {
-            if range.contains(&time) {
-                current_prayer = prayer;
-            }
+    /// Helper function for `time_remaining_hms_with`
+    fn remaining_hms(&self, now: DateTime) -> Result<(u32, u32, u32), crate::Error> {
+        let seconds = u32::try_from(self.duration_to_next(now)?.num_seconds()).unwrap_or(0);
+        Ok((seconds / 3600, seconds / 60 % 60, seconds % 60))
+    }
+    /// The next prayer, when it starts and how long until then
+    pub fn countdown(&self, now: DateTime) -> Result<Countdown, crate::Error> {
+        let prayer = self.next_time(now);
+        let at = match prayer {
+            // once today's Fajr has passed, the next one is tomorrow's
+            Prayer::Fajr if self.fajr <= now => self.fajr_tomorrow,
+            prayer => self.time(prayer),
+        };
+        if at <= now {
+            return Err(crate::Error::InvalidArgument(format!(
+                "{now} is past the computed schedule"
+            )));
         }
-        current_prayer
+        Ok(Countdown {
+            prayer,
+            at,
+            remaining: at - now,
+        })
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::salah::{madhab::Madhab, method::Method};
-    use crate::time;
-
-    fn date() -> Result<Date, crate::Error> {
-        time::date(2021, 4, 9)
+    /// Remaining time to next prayer, spelled out in the given language
+    pub fn time_remaining_human(&self, lang: Language) -> Result<String, crate::Error> {
+        let (hours, minutes) = self.time_remaining()?;
+        Ok(lang.duration(hours, minutes))
     }
-    fn city() -> Result<Location, crate::Error> {
-        let jakarta = Location::new(-6.18233995_f32, 106.84287154_f32);
-        Ok(jakarta)
+    /// Start of the Islamic day, today's Maghreb.
+    /// Anything after it belongs to the next Hijri date.
+    pub const fn islamic_day_start(&self) -> DateTime {
+        self.maghreb
+    }
+    /// Solar midnight, the sun's anti-transit twelve hours after Dohr.
+    /// Not to be confused with `midnight`, the middle of the night.
+    pub fn solar_midnight(&self) -> DateTime {
+        self.dohr + Duration::hours(12)
+    }
+    /// Time left until sunrise (Sherook), or `None` once the sun is up
+    pub fn until_sunrise(&self, now: DateTime) -> Option<Duration> {
+        if now < self.sherook {
+            Some(self.sherook - now)
+        } else {
+            None
+        }
+    }
+    /// Length of the morning (Fajr to Sherook) and evening (Maghreb to Ishaa) twilight
+    pub fn twilight_duration(&self) -> (Duration, Duration) {
+        (self.sherook - self.fajr, self.ishaa - self.maghreb)
+    }
+    /// Time the sun is up, from Sherook to Maghreb
+    pub fn daylight_duration(&self) -> Duration {
+        self.maghreb - self.sherook
+    }
+    /// Time the sun is down, from Maghreb to tomorrow's Sherook
+    pub fn night_duration(&self) -> Duration {
+        self.sherook_tomorrow - self.maghreb
+    }
+    /// The recommended (sunnah) times
+    pub fn sunnah(&self) -> SunnahTimes {
+        SunnahTimes {
+            imsak: self.imsak,
+            ishraq: self.ishraq,
+            duha: self.duha,
+            first_third_of_night: self.first_third_of_night,
+            midnight: self.midnight,
+            last_third_of_night: self.last_third_of_night,
+            tahajjud: self.tahajjud_window(),
+        }
+    }
+    /// Whether fasting is forbidden on this date:
+    /// Eid al-Fitr (1 Shawwal), Eid al-Adha and the days of Tashreeq (10-13 Dhul-Hijja)
+    pub fn fasting_forbidden(&self) -> bool {
+        let hijri = self.hijri_date();
+        matches!((hijri.month, hijri.day), (10, 1) | (12, 10..=13))
+    }
+    /// Whether the date falls in Ramadan, following the configured Hijri adjustment
+    pub fn is_ramadan(&self) -> bool {
+        self.hijri_date().month == 9
+    }
+    /// Hijri date of the computed day
+    fn hijri_date(&self) -> HijriDate {
+        HijriDate::from_gregorian(self.date.date(), self.config.hijri_adjustment)
+    }
+    /// The Tahajjud window, from the configured start until tomorrow's Fajr
+    pub fn tahajjud_window(&self) -> (DateTime, DateTime) {
+        let start = match self.config.tahajjud_start {
+            TahajjudStart::Ishaa => self.ishaa,
+            TahajjudStart::Midnight => self.midnight,
+            TahajjudStart::LastThirdOfNight => self.last_third_of_night,
+        };
+        (self.night_time(start), self.fajr_tomorrow)
+    }
+    /// Night times past midnight are stored on the same date,
+    /// move them to the following day
+    fn night_time(&self, time: DateTime) -> DateTime {
+        if time < self.maghreb {
+            time + Duration::days(1)
+        } else {
+            time
+        }
+    }
+    /// Shift every time from the `from_tz` UTC offset to the `to_tz` one, both in hours
+    pub fn convert_timezone(&self, from_tz: f32, to_tz: f32) -> Self {
+        let shift = Duration::seconds(((to_tz - from_tz) * 3600.0).round() as i64);
+        self.map_times(|time| time + shift)
+    }
+    /// Every prayer time in the IANA time zone `zone`, such as `Asia/Jakarta`
+    #[cfg(feature = "tz")]
+    pub fn in_zone(
+        &self,
+        zone: &str,
+    ) -> Result<[(Prayer, chrono::DateTime<chrono_tz::Tz>); 6], crate::Error> {
+        use chrono::TimeZone;
+
+        let tz: chrono_tz::Tz = zone
+            .parse()
+            .map_err(|_| crate::Error::InvalidArgument(format!("Unknown time zone: {zone}")))?;
+        let offset = Self::utc_offset(self.date, self.location, self.config)
+            + Self::summer_hours(self.date, self.config);
+        let offset = Duration::seconds((offset * 3600.0).round() as i64);
+        Ok(PRAYERS.map(|prayer| (prayer, tz.from_utc_datetime(&(self.time(prayer) - offset)))))
+    }
+    /// The same schedule in standard time, recomputed without the summer hour
+    pub fn without_dst(&self) -> Result<Self, crate::Error> {
+        let config = self.config.with_summer_time(false);
+        Self::new(self.date.date(), self.location, config)
+    }
+    /// Every time as UTC, without the location's offset or daylight saving.
+    /// Dates follow, so times before midnight UTC fall on the previous day.
+    pub fn in_utc(&self) -> Self {
+        let offset = Self::utc_offset(self.date, self.location, self.config)
+            + Self::summer_hours(self.date, self.config);
+        let mut times = *self;
+        for time in [
+            &mut times.ishaa,
+            &mut times.first_third_of_night,
+            &mut times.midnight,
+            &mut times.last_third_of_night,
+        ] {
+            *time = self.night_time(*time);
+        }
+        times.convert_timezone(offset, 0.0)
+    }
+    /// Apply `f` to every computed time
+    fn map_times(mut self, f: impl Fn(DateTime) -> DateTime) -> Self {
+        for time in [
+            &mut self.dohr,
+            &mut self.asr,
+            &mut self.asr_secondary,
+            &mut self.maghreb,
+            &mut self.ishaa,
+            &mut self.fajr,
+            &mut self.imsak,
+            &mut self.fajr_tomorrow,
+            &mut self.sherook_tomorrow,
+            &mut self.sherook,
+            &mut self.ishraq,
+            &mut self.duha,
+            &mut self.first_third_of_night,
+            &mut self.midnight,
+            &mut self.last_third_of_night,
+        ] {
+            *time = f(*time);
+        }
+        self.asr_hanafi = self.asr_hanafi.map(&f);
+        self
+    }
+    /// The wall clock at the location, independent of the machine's time zone
+    pub const fn clock(&self) -> LocationClock {
+        LocationClock::new(self.location, self.config)
+    }
+    /// Get next prayer
+    pub fn next(&self) -> Result<Prayer, crate::Error> {
+        self.next_with(&self.clock())
+    }
+    /// Get next prayer, reading the current time from `clock`
+    pub fn next_with(&self, clock: &impl Clock) -> Result<Prayer, crate::Error> {
+        Ok(self.next_time(clock.now()))
+    }
+    /// Helper function for `next`
+    fn next_time(&self, time: DateTime) -> Prayer {
+        match self.current_time(time) {
+            Some(Prayer::Fajr) => Prayer::Sherook,
+            Some(Prayer::Sherook) => Prayer::Dohr,
+            Some(Prayer::Dohr) => Prayer::Asr,
+            Some(Prayer::Asr) => Prayer::Maghreb,
+            Some(Prayer::Maghreb) => Prayer::Ishaa,
+            // after Ishaa ended, only Fajr is left
+            Some(Prayer::Ishaa) | None => Prayer::Fajr,
+        }
+    }
+    /// Get the latest prayer that has begun
+    pub fn previous(&self) -> Result<Prayer, crate::Error> {
+        self.previous_with(&self.clock())
+    }
+    /// Get the latest prayer that has begun, reading the current time from `clock`
+    pub fn previous_with(&self, clock: &impl Clock) -> Result<Prayer, crate::Error> {
+        Ok(self.previous_time(clock.now()))
+    }
+    /// Helper function for `previous`
+    fn previous_time(&self, time: DateTime) -> Prayer {
+        match self.next_time(time) {
+            // before Fajr, the last prayer was the previous night's Ishaa
+            Prayer::Fajr => Prayer::Ishaa,
+            Prayer::Sherook => Prayer::Fajr,
+            Prayer::Dohr => Prayer::Sherook,
+            Prayer::Asr => Prayer::Dohr,
+            Prayer::Maghreb => Prayer::Asr,
+            Prayer::Ishaa => Prayer::Maghreb,
+        }
+    }
+    /// Calculation method the times were computed with
+    pub const fn method(&self) -> Method {
+        self.config.method
+    }
+    /// Get prayer's time
+    pub fn time(&self, prayer: Prayer) -> DateTime {
+        self[prayer]
+    }
+    /// Minutes tomorrow's `prayer` is later (positive) or earlier (negative) than today's
+    pub fn delta_to_tomorrow(&self, prayer: Prayer) -> Result<i64, crate::Error> {
+        let tomorrow = match prayer {
+            Prayer::Fajr => self.fajr_tomorrow,
+            _ => {
+                let date = self.date.date() + Duration::days(1);
+                Self::new(date, self.location, self.config)?.time(prayer)
+            }
+        };
+        let delta = tomorrow - self.time(prayer) - Duration::days(1);
+        Ok((delta.num_seconds() as f64 / 60.0).round() as i64)
+    }
+    /// The six daily times, from Fajr to Ishaa
+    pub fn iter(&self) -> impl Iterator<Item = (Prayer, DateTime)> + '_ {
+        PRAYERS
+            .into_iter()
+            .map(move |prayer| (prayer, self[prayer]))
+    }
+    /// Prayers keyed and ordered by their time
+    pub fn by_time(&self) -> BTreeMap<DateTime, Prayer> {
+        PRAYERS
+            .iter()
+            .map(|&prayer| (self.time(prayer), prayer))
+            .collect()
+    }
+    /// When to fire the reminder of each prayer, following the configured leads
+    pub fn notification_times(&self) -> Vec<(Prayer, DateTime)> {
+        let leads = self.config.notification_leads;
+        PRAYERS
+            .iter()
+            .map(|&prayer| {
+                let lead = Duration::minutes(leads.minutes(prayer).into());
+                (prayer, self.time(prayer) - lead)
+            })
+            .collect()
+    }
+    /// Prayers still ahead of `now`, through tomorrow's Fajr
+    pub fn from_now(&self, now: DateTime) -> Vec<(Prayer, DateTime)> {
+        PRAYERS
+            .iter()
+            .map(|&prayer| (prayer, self.time(prayer)))
+            .chain(std::iter::once((Prayer::Fajr, self.fajr_tomorrow)))
+            .filter(|&(_, time)| time >= now)
+            .collect()
+    }
+    /// The prayer starting within `within` after `now`, if any
+    pub fn starting_soon(&self, now: DateTime, within: Duration) -> Option<Prayer> {
+        self.from_now(now)
+            .into_iter()
+            .find(|&(_, time)| time <= now + within)
+            .map(|(prayer, _)| prayer)
+    }
+    /// Prayer times in 24-hour clock, `04:36:34` or `04:36` without seconds
+    pub fn formatted(&self, seconds: bool) -> Vec<(Prayer, String)> {
+        self.format_times(if seconds { "%H:%M:%S" } else { "%H:%M" })
+    }
+    /// The date and the six times as a CSV row, in the columns of `month_csv`
+    pub fn to_csv_row(&self) -> String {
+        let times = self.formatted(true).into_iter().map(|(_, time)| time);
+        std::iter::once(self.date.format("%Y-%m-%d").to_string())
+            .chain(times)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+    /// Prayer times in 12-hour clock, e.g. `04:36 AM`
+    pub fn formatted_12h(&self) -> Vec<(Prayer, String)> {
+        self.format_times("%I:%M %p")
+    }
+    fn format_times(&self, format: &str) -> Vec<(Prayer, String)> {
+        PRAYERS
+            .iter()
+            .map(|&prayer| (prayer, self.time(prayer).format(format).to_string()))
+            .collect()
+    }
+    /// Get current prayer
+    pub fn current(&self) -> Result<Prayer, crate::Error> {
+        self.current_with(&self.clock())
+    }
+    /// Get current prayer, reading the current time from `clock`
+    pub fn current_with(&self, clock: &impl Clock) -> Result<Prayer, crate::Error> {
+        self.current_time(clock.now())
+            .ok_or(crate::Error::NoCurrentPrayer)
+    }
+    /// When the window of the current prayer closes. Ishaa ends at tomorrow's Fajr,
+    /// or at midnight with `IshaaEnd::Midnight`.
+    pub fn current_ends_at(&self, now: DateTime) -> Result<DateTime, crate::Error> {
+        self.current_window(now)
+            .map(|(_, window)| window.end)
+            .ok_or(crate::Error::NoCurrentPrayer)
+    }
+    /// How far through the current prayer window `now` is, from 0.0 to 1.0
+    pub fn current_progress(&self, now: DateTime) -> Result<f32, crate::Error> {
+        let (_, window) = self
+            .current_window(now)
+            .ok_or(crate::Error::NoCurrentPrayer)?;
+        let elapsed = (now - window.start).num_seconds() as f32;
+        let length = (window.end - window.start).num_seconds() as f32;
+        Ok(elapsed / length)
+    }
+    /// Helper function for `current`
+    fn current_time(&self, time: DateTime) -> Option<Prayer> {
+        self.current_window(time).map(|(prayer, _)| prayer)
+    }
+    /// The prayer whose window contains `time`, and that window
+    fn current_window(&self, time: DateTime) -> Option<(Prayer, Range<DateTime>)> {
+        // before Fajr, it is still Ishaa of the previous evening
+        let end = match self.config.ishaa_end {
+            IshaaEnd::Fajr => self.fajr,
+            IshaaEnd::Midnight => self.night_time(self.midnight) - Duration::days(1),
+        };
+        let previous_night = self.night_time(self.ishaa) - Duration::days(1)..end;
+
+        PRAYERS
+            .into_iter()
+            .map(|prayer| (prayer, self.window(prayer)))
+            .find(|(_, window)| window.contains(&time))
+            .or_else(|| {
+                previous_night
+                    .contains(&time)
+                    .then_some((Prayer::Ishaa, previous_night))
+            })
+    }
+    /// Length of each prayer window as a fraction of a day
+    pub fn window_percentages(&self) -> Vec<(Prayer, f32)> {
+        PRAYERS
+            .iter()
+            .map(|&prayer| {
+                let window = self.window(prayer);
+                let seconds = (window.end - window.start).num_seconds() as f32;
+                (prayer, seconds / 86_400.0)
+            })
+            .collect()
+    }
+    /// Window of `prayer` widened by `pad` on each side, so notifications aren't missed
+    pub fn window_padded(&self, prayer: Prayer, pad: Duration) -> Range<DateTime> {
+        let window = self.window(prayer);
+        window.start - pad..window.end + pad
+    }
+    /// Time range in which the prayer is the current one.
+    /// Ishaa past midnight is moved to the following day.
+    fn window(&self, prayer: Prayer) -> Range<DateTime> {
+        let ishaa = self.night_time(self.ishaa);
+        match prayer {
+            Prayer::Fajr => self.fajr..self.sherook,
+            Prayer::Sherook => self.sherook..self.dohr,
+            Prayer::Dohr => self.dohr..self.asr,
+            Prayer::Asr => self.asr..self.maghreb,
+            Prayer::Maghreb => self.maghreb..ishaa,
+            Prayer::Ishaa => match self.config.ishaa_end {
+                IshaaEnd::Fajr => ishaa..self.fajr_tomorrow,
+                IshaaEnd::Midnight => ishaa..self.night_time(self.midnight),
+            },
+        }
+    }
+}
+
+impl Index<Prayer> for PrayerTimes {
+    type Output = DateTime;
+
+    fn index(&self, prayer: Prayer) -> &DateTime {
+        match prayer {
+            Prayer::Fajr => &self.fajr,
+            Prayer::Sherook => &self.sherook,
+            Prayer::Dohr => &self.dohr,
+            Prayer::Asr => &self.asr,
+            Prayer::Maghreb => &self.maghreb,
+            Prayer::Ishaa => &self.ishaa,
+        }
+    }
+}
+
+/// Serialized form of `PrayerTimes`, each time an RFC3339 string carrying
+/// the location's UTC offset on its date
+#[cfg(feature = "serde")]
+mod rfc3339 {
+    use chrono::{FixedOffset, TimeZone};
+    use serde::{ser::Error as _, Deserialize, Serialize, Serializer};
+
+    use super::{Config, Duration, Location, Sun};
+
+    type Time = chrono::DateTime<FixedOffset>;
+
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct PrayerTimes {
+        date: Time,
+        location: Location,
+        config: Config,
+        dohr: Time,
+        asr: Time,
+        asr_secondary: Time,
+        asr_hanafi: Option<Time>,
+        maghreb: Time,
+        ishaa: Time,
+        fajr: Time,
+        imsak: Time,
+        fajr_tomorrow: Time,
+        sherook_tomorrow: Time,
+        sherook: Time,
+        ishraq: Time,
+        duha: Time,
+        first_third_of_night: Time,
+        midnight: Time,
+        last_third_of_night: Time,
+    }
+
+    impl TryFrom<super::PrayerTimes> for PrayerTimes {
+        type Error = crate::Error;
+
+        fn try_from(times: super::PrayerTimes) -> Result<Self, Self::Error> {
+            let offset = |date| {
+                let hours = super::PrayerTimes::utc_offset(date, times.location, times.config)
+                    + super::PrayerTimes::summer_hours(date, times.config);
+                FixedOffset::east_opt((hours * 3600.0).round() as i32).ok_or(
+                    crate::Error::InvalidArgument(format!("UTC offset out of range: {hours}")),
+                )
+            };
+            let (today, tomorrow) = (offset(times.date)?, offset(times.date + Duration::days(1))?);
+            let at = |offset: FixedOffset, time| {
+                offset
+                    .from_utc_datetime(&(time - Duration::seconds(offset.local_minus_utc().into())))
+            };
+            Ok(Self {
+                date: at(today, times.date),
+                location: times.location,
+                config: times.config,
+                dohr: at(today, times.dohr),
+                asr: at(today, times.asr),
+                asr_secondary: at(today, times.asr_secondary),
+                asr_hanafi: times.asr_hanafi.map(|time| at(today, time)),
+                maghreb: at(today, times.maghreb),
+                ishaa: at(today, times.ishaa),
+                fajr: at(today, times.fajr),
+                imsak: at(today, times.imsak),
+                fajr_tomorrow: at(tomorrow, times.fajr_tomorrow),
+                sherook_tomorrow: at(tomorrow, times.sherook_tomorrow),
+                sherook: at(today, times.sherook),
+                ishraq: at(today, times.ishraq),
+                duha: at(today, times.duha),
+                first_third_of_night: at(today, times.first_third_of_night),
+                midnight: at(today, times.midnight),
+                last_third_of_night: at(today, times.last_third_of_night),
+            })
+        }
+    }
+
+    impl TryFrom<PrayerTimes> for super::PrayerTimes {
+        type Error = crate::Error;
+
+        fn try_from(times: PrayerTimes) -> Result<Self, Self::Error> {
+            let date = times.date.naive_local();
+            Ok(Self {
+                date,
+                location: times.location,
+                config: times.config,
+                dohr: times.dohr.naive_local(),
+                asr: times.asr.naive_local(),
+                asr_secondary: times.asr_secondary.naive_local(),
+                asr_hanafi: times.asr_hanafi.map(|time| time.naive_local()),
+                maghreb: times.maghreb.naive_local(),
+                ishaa: times.ishaa.naive_local(),
+                fajr: times.fajr.naive_local(),
+                imsak: times.imsak.naive_local(),
+                fajr_tomorrow: times.fajr_tomorrow.naive_local(),
+                sherook_tomorrow: times.sherook_tomorrow.naive_local(),
+                sherook: times.sherook.naive_local(),
+                ishraq: times.ishraq.naive_local(),
+                duha: times.duha.naive_local(),
+                first_third_of_night: times.first_third_of_night.naive_local(),
+                midnight: times.midnight.naive_local(),
+                last_third_of_night: times.last_third_of_night.naive_local(),
+                sun: Sun::new(date, times.config)?,
+                sun_tomorrow: Sun::new(date + Duration::days(1), times.config)?,
+            })
+        }
+    }
+
+    impl Serialize for super::PrayerTimes {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            PrayerTimes::try_from(*self)
+                .map_err(S::Error::custom)?
+                .serialize(serializer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::salah::config::{Adjustments, HighLatitudeRule, NotificationLeads};
+    use crate::{time, FixedClock};
+
+    fn date() -> Result<Date, crate::Error> {
+        time::date(2021, 4, 9)
+    }
+    fn city() -> Result<Location, crate::Error> {
+        let jakarta = Location::new(-6.18233995_f32, 106.84287154_f32);
+        Ok(jakarta)
     }
     fn prayer_times(config: Config) -> Result<PrayerTimes, crate::Error> {
-        let prayer_times = PrayerSchedule::new(city()?)?
-            .on(date()?)
+        let clock = FixedClock(expected_time(12, 0, 0)?);
+        let prayer_times = PrayerSchedule::new_with(city()?, &clock)?
             .with_config(config)
             .calculate()?;
         Ok(prayer_times)
@@ -379,6 +1649,22 @@ mod tests {
         date.and_hms_opt(hour, minute, second)
             .ok_or(crate::Error::InvalidTime)
     }
+    /// Every prayer within a minute of the published `(hour, minute)`
+    fn assert_timetable(
+        prayer_times: &PrayerTimes,
+        timetable: [(u32, u32); 6],
+    ) -> Result<(), crate::Error> {
+        let date = prayer_times.date.date();
+        for (prayer, (hour, minute)) in PRAYERS.into_iter().zip(timetable) {
+            let published = expected_time_with_date(date, hour, minute, 0)?;
+            let difference = prayer_times.time(prayer) - published;
+            assert!(
+                difference.num_seconds().abs() <= 60,
+                "{prayer:?} off by {difference}"
+            );
+        }
+        Ok(())
+    }
     fn expected_time_with_date(
         date: Date,
         hour: u32,
@@ -441,64 +1727,1682 @@ mod tests {
         Ok(())
     }
     #[test]
-    fn current_prayer_is_dohr() -> Result<(), crate::Error> {
-        // Dohr is: 2021-04-19T11:51:45+07:00
-        let date = time::date(2021, 4, 19)?;
+    fn asr_lower_limb() -> Result<(), crate::Error> {
         let config = Config::new().with(Method::Singapore, Madhab::Shafi);
-        let prayer_times = prayer_times_with_date(config, date)?;
-        let current_prayer_time = expected_time(11, 52, 0)?;
+        let center = prayer_times(config)?;
+        let lower_limb = prayer_times(config.asr_lower_limb(true))?;
 
-        assert_eq!(prayer_times.current_time(current_prayer_time), Prayer::Dohr);
+        let shift = (center.asr - lower_limb.asr).num_seconds();
+        assert!(shift > 0 && shift < 120, "asr shifted by {shift}s");
+        assert_eq!(center.dohr, lower_limb.dohr);
+        assert_eq!(center.maghreb, lower_limb.maghreb);
         Ok(())
     }
     #[test]
-    fn current_prayer_is_asr() -> Result<(), crate::Error> {
-        // Asr is: 2021-04-19T15:11:51+07:00
-        let date = time::date(2021, 4, 19)?;
+    fn until_sunrise_before_dawn() -> Result<(), crate::Error> {
         let config = Config::new().with(Method::Singapore, Madhab::Shafi);
-        let prayer_times = prayer_times_with_date(config, date)?;
-        let current_prayer_time = expected_time_with_date(date, 15, 13, 0)?;
+        let prayer_times = prayer_times(config)?;
 
-        assert_eq!(prayer_times.current_time(current_prayer_time), Prayer::Asr);
+        let until_sunrise = prayer_times.until_sunrise(expected_time(4, 54, 14)?);
+        assert_eq!(until_sunrise, Some(Duration::hours(1)));
         Ok(())
     }
     #[test]
-    fn current_prayer_is_maghreb() -> Result<(), crate::Error> {
-        // Maghreb is: 2021-04-19T17:50:12+07:00
-        let date = time::date(2021, 4, 19)?;
+    fn until_sunrise_after_sunrise() -> Result<(), crate::Error> {
         let config = Config::new().with(Method::Singapore, Madhab::Shafi);
-        let prayer_times = prayer_times_with_date(config, date)?;
-        let current_prayer_time = expected_time_with_date(date, 17, 51, 0)?;
+        let prayer_times = prayer_times(config)?;
+
+        assert_eq!(prayer_times.until_sunrise(expected_time(7, 0, 0)?), None);
+        assert_eq!(prayer_times.until_sunrise(prayer_times.sherook), None);
+        Ok(())
+    }
+    #[test]
+    fn formatted_12h() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let formatted = prayer_times(config)?.formatted_12h();
+
+        assert_eq!(formatted.len(), 6);
+        assert_eq!(formatted[0], (Prayer::Fajr, "04:36 AM".to_string()));
+        assert_eq!(formatted[4], (Prayer::Maghreb, "05:54 PM".to_string()));
+        Ok(())
+    }
+    #[test]
+    /// Every time is derived from a single dohr computation,
+    /// this locks the results from before that refactor
+    fn praytimes_jakarta_egyptian() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Egyptian, Madhab::Hanafi);
+        let prayer_times = prayer_times(config)?;
 
+        assert_eq!(prayer_times.fajr, expected_time(4, 38, 36)?);
+        assert_eq!(prayer_times.sherook, expected_time(5, 54, 14)?);
+        assert_eq!(prayer_times.dohr, expected_time(11, 54, 14)?);
+        assert_eq!(prayer_times.asr, expected_time(16, 13, 3)?);
+        assert_eq!(prayer_times.maghreb, expected_time(17, 54, 14)?);
+        assert_eq!(prayer_times.ishaa, expected_time(19, 1, 47)?);
         assert_eq!(
-            prayer_times.current_time(current_prayer_time),
-            Prayer::Maghreb
+            prayer_times.fajr_tomorrow,
+            expected_time_with_date(time::date(2021, 4, 10)?, 4, 38, 25)?
         );
+        assert_eq!(prayer_times.first_third_of_night, expected_time(21, 29, 1)?);
+        assert_eq!(prayer_times.midnight, expected_time(23, 16, 25)?);
+        assert_eq!(prayer_times.last_third_of_night, expected_time(1, 3, 49)?);
+
         Ok(())
     }
     #[test]
-    fn current_prayer_is_ishaa() -> Result<(), crate::Error> {
-        // Ishaa is: 2021-04-19T19:00:27+07:00
-        let date = time::date(2021, 4, 19)?;
+    fn praytimes_invalid_angle() -> Result<(), crate::Error> {
+        let config = Config::new().angle(95.0, 18.0);
+
+        let err = prayer_times(config).unwrap_err();
+        assert_eq!(err, crate::Error::InvalidAngle(95.0));
+        Ok(())
+    }
+    #[test]
+    fn praytimes_for_locations() -> Result<(), crate::Error> {
         let config = Config::new().with(Method::Singapore, Madhab::Shafi);
-        let prayer_times = prayer_times_with_date(config, date)?;
-        let current_prayer_time = expected_time_with_date(date, 19, 1, 0)?;
+        let locations = [
+            city()?,
+            Location::new(-7.797068, 110.370529),
+            Location::new(3.595196, 98.672226),
+        ];
+
+        let shared = PrayerTimes::for_locations(date()?, &locations, config)?;
+        assert_eq!(shared.len(), locations.len());
+        for (location, times) in locations.into_iter().zip(shared) {
+            let naive = PrayerTimes::new(date()?, location, config)?;
+            assert_eq!(format!("{times:?}"), format!("{naive:?}"));
+        }
+        Ok(())
+    }
+    #[test]
+    fn convert_timezone() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+        let utc = prayer_times.convert_timezone(7.0, 0.0);
 
+        for prayer in PRAYERS {
+            assert_eq!(
+                prayer_times.time(prayer) - utc.time(prayer),
+                Duration::hours(7)
+            );
+        }
+        assert_eq!(utc.dohr, expected_time(4, 54, 14)?);
         assert_eq!(
-            prayer_times.current_time(current_prayer_time),
-            Prayer::Ishaa
+            utc.fajr,
+            expected_time_with_date(time::date(2021, 4, 8)?, 21, 36, 34)?
         );
         Ok(())
     }
     #[test]
-    fn current_prayer_is_fajr() -> Result<(), crate::Error> {
-        // Fajr is: 2021-04-19T04:34:54+07:00,
-        let date = time::date(2021, 4, 19)?;
+    fn tahajjud_window() -> Result<(), crate::Error> {
         let config = Config::new().with(Method::Singapore, Madhab::Shafi);
-        let prayer_times = prayer_times_with_date(config, date)?;
-        let current_prayer_time = expected_time_with_date(date, 4, 35, 0)?;
+        let tomorrow = time::date(2021, 4, 10)?;
+
+        let midnight = prayer_times(config)?;
+        let (start, end) = midnight.tahajjud_window();
+        assert_eq!(start, midnight.midnight);
+        assert_eq!(end, midnight.fajr_tomorrow);
+
+        let last_third = prayer_times(config.tahajjud_start(TahajjudStart::LastThirdOfNight))?;
+        let (start, end) = last_third.tahajjud_window();
+        assert_eq!(start, expected_time_with_date(tomorrow, 1, 2, 28)?);
+        assert_eq!(end, last_third.fajr_tomorrow);
+        Ok(())
+    }
+    #[test]
+    fn praytimes_at_epoch() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        // 2021-04-08T20:00:00Z, already 2021-04-09 in Jakarta
+        let prayer_times = PrayerTimes::at_epoch(1_617_912_000, city()?, config)?;
+
+        assert_eq!(prayer_times.date, expected_time(0, 0, 0)?);
+        assert_eq!(prayer_times.dohr, expected_time(11, 54, 14)?);
+        assert_eq!(prayer_times.fajr, expected_time(4, 36, 34)?);
+        assert_eq!(prayer_times.maghreb, expected_time(17, 54, 14)?);
+        Ok(())
+    }
+    #[test]
+    fn at_epoch_in_summer_time() -> Result<(), crate::Error> {
+        // 2021-06-01T23:30:00Z, already 2021-06-02 on a London summer clock
+        let config = Config::new()
+            .with(Method::MuslimWorldLeague, Madhab::Shafi)
+            .with_timezone(0.0);
+        let london = Location::new(51.5074, -0.1278);
+        let rule = config.with_high_latitude_rule(HighLatitudeRule::SeventhOfNight);
+
+        let winter = PrayerTimes::at_epoch(1_622_590_200, london, rule)?;
+        assert_eq!(winter.date.date(), time::date(2021, 6, 1)?);
+        let summer = PrayerTimes::at_epoch(1_622_590_200, london, rule.with_summer_time(true))?;
+        assert_eq!(summer.date.date(), time::date(2021, 6, 2)?);
+        Ok(())
+    }
+    #[cfg(feature = "tz")]
+    #[test]
+    fn at_epoch_across_dst() -> Result<(), crate::Error> {
+        let config = Config::new()
+            .with(Method::MuslimWorldLeague, Madhab::Shafi)
+            .with_high_latitude_rule(HighLatitudeRule::SeventhOfNight)
+            .with_zone(chrono_tz::Europe::London);
+        let london = Location::new(51.5074, -0.1278);
+
+        // 2021-03-27T23:30:00Z is still the 27th in winter time
+        let winter = PrayerTimes::at_epoch(1_616_887_800, london, config)?;
+        assert_eq!(winter.date.date(), time::date(2021, 3, 27)?);
+        // 2021-06-01T23:30:00Z is already the 2nd in summer time
+        let summer = PrayerTimes::at_epoch(1_622_590_200, london, config)?;
+        assert_eq!(summer.date.date(), time::date(2021, 6, 2)?);
+        Ok(())
+    }
+    #[test]
+    fn twilight_duration() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let (morning, evening) = prayer_times(config)?.twilight_duration();
 
-        assert_eq!(prayer_times.current_time(current_prayer_time), Prayer::Fajr);
+        assert_eq!(morning, Duration::seconds(4660));
+        assert_eq!(evening, Duration::seconds(4175));
+        // Close to the equator, twilight lasts well under two hours
+        for twilight in [morning, evening] {
+            assert!(twilight > Duration::minutes(60) && twilight < Duration::minutes(90));
+        }
+        Ok(())
+    }
+    #[test]
+    fn ishaa_end() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let after_midnight = expected_time_with_date(time::date(2021, 4, 10)?, 0, 30, 0)?;
+
+        let until_fajr = prayer_times(config)?;
+        assert_eq!(until_fajr.current_time(after_midnight), Some(Prayer::Ishaa));
+        assert_eq!(until_fajr.next_time(after_midnight), Prayer::Fajr);
+
+        let until_midnight = prayer_times(config.ishaa_end(IshaaEnd::Midnight))?;
+        assert_eq!(until_midnight.current_time(after_midnight), None);
+        assert_eq!(until_midnight.next_time(after_midnight), Prayer::Fajr);
+        assert_eq!(
+            until_midnight.current_time(expected_time(23, 0, 0)?),
+            Some(Prayer::Ishaa)
+        );
+        Ok(())
+    }
+    #[test]
+    fn grid() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let grid = compute_grid(-10.0..=-5.0, 100.0..=105.0, 5.0, date()?, config)?;
+
+        assert_eq!(grid.len(), 4);
+        assert_eq!(grid[0].0, Location::new(-10.0, 100.0));
+        assert_eq!(grid[3].0, Location::new(-5.0, 105.0));
+        let fajrs = grid
+            .into_iter()
+            .map(|(_, times)| times.map(|times| times.fajr))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (i, a) in fajrs.iter().enumerate() {
+            for b in &fajrs[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+
+        // the pole fails on its own, the rest of the column still computes
+        let grid = compute_grid(0.0..=90.0, 0.0..=0.0, 45.0, date()?, config)?;
+        assert_eq!(grid.len(), 3);
+        assert!(grid[0].1.is_ok() && grid[1].1.is_ok());
+        assert_eq!(grid[2].1.as_ref().err(), Some(&crate::Error::PolarLocation));
+        Ok(())
+    }
+    #[test]
+    fn grid_invalid_step() -> Result<(), crate::Error> {
+        let config = Config::new();
+        let err = compute_grid(-10.0..=-5.0, 100.0..=105.0, 0.0, date()?, config).unwrap_err();
+
+        assert_eq!(
+            err,
+            crate::Error::InvalidArgument("Grid step must be positive, got 0".to_string())
+        );
+        Ok(())
+    }
+    #[test]
+    fn fasting_forbidden_on_eid() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        // 1 Shawwal 1442, 10 and 13 Dhul-Hijja 1442
+        for date in [
+            time::date(2021, 5, 13)?,
+            time::date(2021, 7, 20)?,
+            time::date(2021, 7, 23)?,
+        ] {
+            assert!(prayer_times_with_date(config, date)?.fasting_forbidden());
+        }
+        Ok(())
+    }
+    #[test]
+    fn hijri_adjustment() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::UmmAlQura, Madhab::Shafi);
+        let eve = HijriDate::new(1442, 9, 1)?.to_gregorian()? - Duration::days(1);
+
+        let times = prayer_times_with_date(config, eve)?;
+        assert_eq!(times.ishaa - times.maghreb, Duration::minutes(90));
+
+        // moon sighted a day early, Ramadan has already begun
+        let times = prayer_times_with_date(config.with_hijri_adjustment(1), eve)?;
+        assert_eq!(times.ishaa - times.maghreb, Duration::minutes(120));
+        Ok(())
+    }
+    #[test]
+    fn is_ramadan() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        // Ramadan 1443 ran from 2 April to 1 May 2022
+        assert!(prayer_times_with_date(config, time::date(2022, 4, 15)?)?.is_ramadan());
+        assert!(!prayer_times_with_date(config, time::date(2022, 6, 1)?)?.is_ramadan());
+        Ok(())
+    }
+    #[test]
+    fn fasting_allowed_on_ordinary_day() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+
+        assert!(!prayer_times(config)?.fasting_forbidden());
+        // 14 Dhul-Hijja 1442
+        let after_tashreeq = prayer_times_with_date(config, time::date(2021, 7, 25)?)?;
+        assert!(!after_tashreeq.fasting_forbidden());
+        Ok(())
+    }
+    #[test]
+    fn formatted() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+
+        let formatted = prayer_times.formatted(false);
+        assert_eq!(formatted[0], (Prayer::Fajr, "04:36".to_string()));
+        assert_eq!(formatted[4], (Prayer::Maghreb, "17:54".to_string()));
+
+        let formatted = prayer_times.formatted(true);
+        assert_eq!(formatted[0], (Prayer::Fajr, "04:36:34".to_string()));
+        Ok(())
+    }
+    #[test]
+    fn hour_angle() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+
+        assert!(prayer_times.hour_angle(Prayer::Dohr)?.abs() < 1e-3);
+        assert!(prayer_times.hour_angle(Prayer::Fajr)? < -90.0);
+        assert!(prayer_times.hour_angle(Prayer::Asr)? > 0.0);
+        // sunrise and sunset are symmetric around the transit
+        let sherook = prayer_times.hour_angle(Prayer::Sherook)?;
+        let maghreb = prayer_times.hour_angle(Prayer::Maghreb)?;
+        assert!((sherook + maghreb).abs() < 1e-3);
+        assert!((maghreb - 90.0).abs() < 1.0);
+        Ok(())
+    }
+    #[test]
+    fn checked_polar_location() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::MuslimWorldLeague, Madhab::Shafi);
+        // the sun doesn't set near the north pole in June
+        let svalbard = Location::new(78.22, 15.65);
+        let summer = time::date(2021, 6, 21)?;
+
+        assert!(PrayerTimes::checked(summer, svalbard, config).is_none());
+        assert_eq!(
+            PrayerTimes::new(summer, svalbard, config).unwrap_err(),
+            crate::Error::NoValidTime {
+                prayer: Prayer::Maghreb
+            }
+        );
+        assert!(PrayerTimes::checked(date()?, city()?, config).is_some());
+        Ok(())
+    }
+    #[test]
+    fn maghreb_refraction_delta() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let delta = prayer_times(config)?.maghreb_refraction_delta()?;
+
+        // near the equator the sun sets almost vertically, at 15° per hour
+        // 0.8333° takes about 3 minutes 20 seconds
+        assert!(delta > Duration::seconds(195) && delta < Duration::seconds(210));
+
+        // the delta matches the Maghreb actually computed
+        let sunset = |times: &PrayerTimes| times.interpolate_depression(0.0, false);
+        for times in [
+            PrayerTimes::new(date()?, city()?.with_elevation(500.0), config)?,
+            prayer_times(config.with_maghreb_angle(4.0))?,
+        ] {
+            let computed = times.maghreb - sunset(&times)?;
+            let delta = times.maghreb_refraction_delta()?;
+            assert!((computed - delta).num_seconds().abs() <= 1);
+            assert!(delta > Duration::seconds(210));
+        }
+        Ok(())
+    }
+    #[test]
+    fn obliquity() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let june = time::date(2021, 6, 21)?;
+        let december = time::date(2021, 12, 21)?;
+        let dawn = |config: Config, date: Date| -> Result<i64, crate::Error> {
+            let prayer_times = prayer_times_with_date(config, date)?;
+            Ok((prayer_times.dohr - prayer_times.fajr).num_seconds())
+        };
+
+        let seasonal = dawn(config, june)? - dawn(config, december)?;
+        assert!(seasonal.abs() > 5 * 60);
+
+        let untilted = config.with_obliquity(0.0);
+        let seasonal = dawn(untilted, june)? - dawn(untilted, december)?;
+        assert!(seasonal.abs() <= 2);
+        Ok(())
+    }
+    #[test]
+    fn polar_location() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::MuslimWorldLeague, Madhab::Shafi);
+
+        for latitude in [90.0, -90.0, 89.95] {
+            let pole = Location::new(latitude, 0.0);
+            let err = PrayerTimes::new(date()?, pole, config).unwrap_err();
+            assert_eq!(err, crate::Error::PolarLocation);
+        }
+        Ok(())
+    }
+    #[test]
+    fn countdown() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+
+        let countdown = prayer_times.countdown(expected_time(15, 0, 0)?)?;
+        assert_eq!(countdown.prayer, Prayer::Asr);
+        assert_eq!(countdown.at, prayer_times.asr);
+        assert_eq!(countdown.remaining, Duration::seconds(12 * 60 + 14));
+        Ok(())
+    }
+    #[test]
+    fn countdown_across_midnight() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+        let tomorrow = time::date(2021, 4, 10)?;
+
+        for now in [
+            expected_time(23, 0, 0)?,
+            expected_time_with_date(tomorrow, 0, 30, 0)?,
+        ] {
+            let countdown = prayer_times.countdown(now)?;
+            assert_eq!(countdown.prayer, Prayer::Fajr);
+            assert_eq!(countdown.at, prayer_times.fajr_tomorrow);
+            assert!(countdown.remaining > Duration::zero());
+            assert_eq!(countdown.at - countdown.remaining, now);
+        }
+
+        // before dawn, the next Fajr is today's
+        let countdown = prayer_times.countdown(expected_time(3, 0, 0)?)?;
+        assert_eq!(countdown.at, prayer_times.fajr);
+
+        assert!(prayer_times
+            .countdown(expected_time_with_date(tomorrow, 12, 0, 0)?)
+            .is_err());
+        Ok(())
+    }
+    #[test]
+    fn declination_override() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let day_length = |date: Date| -> Result<Duration, crate::Error> {
+            let prayer_times = PrayerTimes::with_declination_override(date, city()?, config, 0.0)?;
+            Ok(prayer_times.maghreb - prayer_times.sherook)
+        };
+
+        let june = day_length(time::date(2021, 6, 21)?)?;
+        let december = day_length(time::date(2021, 12, 21)?)?;
+        assert_eq!(june, december);
+        // with the sun on the celestial equator, only refraction makes the day longer than 12 hours
+        assert!(june > Duration::hours(12) && june < Duration::minutes(12 * 60 + 10));
+
+        let overridden = PrayerTimes::with_declination_override(date()?, city()?, config, 0.0)?;
+        let again = PrayerTimes::with_declination_override(date()?, city()?, config, 0.0)?;
+        assert_eq!(format!("{overridden:?}"), format!("{again:?}"));
+        // on the celestial equator, the sun rises due east
+        assert!((overridden.sunrise_azimuth()? - 90.0).abs() < 1e-3);
+        Ok(())
+    }
+    #[test]
+    fn notification_times() -> Result<(), crate::Error> {
+        let leads = NotificationLeads {
+            fajr: 30,
+            maghreb: 5,
+            ..NotificationLeads::default()
+        };
+        let config = Config::new()
+            .with(Method::Singapore, Madhab::Shafi)
+            .with_notification_leads(leads);
+        let prayer_times = prayer_times(config)?;
+        let notifications = prayer_times.notification_times();
+
+        assert_eq!(notifications.len(), 6);
+        assert_eq!(notifications[0], (Prayer::Fajr, expected_time(4, 6, 34)?));
+        assert_eq!(notifications[2], (Prayer::Dohr, prayer_times.dohr));
+        assert_eq!(
+            notifications[4],
+            (Prayer::Maghreb, prayer_times.maghreb - Duration::minutes(5))
+        );
+        Ok(())
+    }
+    #[test]
+    fn twilight() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+
+        let astronomical = prayer_times.twilight(TwilightKind::Astronomical, true)?;
+        let nautical = prayer_times.twilight(TwilightKind::Nautical, true)?;
+        let civil = prayer_times.twilight(TwilightKind::Civil, true)?;
+        assert!(astronomical < nautical && nautical < civil);
+        assert!(civil < prayer_times.sherook);
+
+        let civil = prayer_times.twilight(TwilightKind::Civil, false)?;
+        let astronomical = prayer_times.twilight(TwilightKind::Astronomical, false)?;
+        assert!(prayer_times.maghreb < civil && civil < astronomical);
+        Ok(())
+    }
+    #[test]
+    fn location_clock() -> Result<(), crate::Error> {
+        let config = Config::new()
+            .with(Method::Singapore, Madhab::Shafi)
+            .with_timezone(7.0);
+        let clock = LocationClock::new(city()?, config);
+        let utc = expected_time(5, 0, 0)?;
+        assert_eq!(clock.at(utc), expected_time(12, 0, 0)?);
+
+        // whatever the machine's zone, the location is seven hours ahead of UTC
+        let ahead = clock.now() - chrono::Utc::now().naive_utc();
+        assert!((ahead - Duration::hours(7)).num_seconds().abs() < 5);
+
+        let summer = LocationClock::new(city()?, config.with_summer_time(true));
+        assert_eq!(summer.at(utc), expected_time(13, 0, 0)?);
+        Ok(())
+    }
+    #[test]
+    fn current_with_clock() -> Result<(), crate::Error> {
+        struct MockClock(DateTime);
+        impl Clock for MockClock {
+            fn now(&self) -> DateTime {
+                self.0
+            }
+        }
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+
+        let clock = MockClock(expected_time(15, 30, 0)?);
+        assert_eq!(prayer_times.current_with(&clock)?, Prayer::Asr);
+        assert_eq!(prayer_times.next_with(&clock)?, Prayer::Maghreb);
+
+        let clock = crate::FixedClock(expected_time(18, 0, 0)?);
+        assert_eq!(prayer_times.current_with(&clock)?, Prayer::Maghreb);
+        Ok(())
+    }
+    #[test]
+    fn location_hash() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let hash = |location: Location| {
+            let mut hasher = DefaultHasher::new();
+            location.hash(&mut hasher);
+            hasher.finish()
+        };
+        let jakarta = Location::new(-6.182_339, 106.842_87);
+        let nearby = Location::new(-6.182_339_5, 106.842_87);
+
+        assert_eq!(jakarta, nearby);
+        assert!(jakarta.approx_eq(&nearby));
+        assert_eq!(hash(jakarta), hash(nearby));
+
+        let bandung = Location::new(-6.917_464, 107.619_125);
+        assert!(!jakarta.approx_eq(&bandung));
+        assert_ne!(hash(jakarta), hash(bandung));
+
+        // an explicit offset changes the times, so it is a different entry
+        let jakarta_wib = Location::with_timezone(-6.182_339, 106.842_87, 7.0);
+        assert!(jakarta.approx_eq(&jakarta_wib));
+        assert_ne!(jakarta, jakarta_wib);
+    }
+    #[test]
+    fn location_cache() -> Result<(), crate::Error> {
+        use std::collections::HashMap;
+
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let mut cache = HashMap::new();
+        cache.insert(city()?, prayer_times(config)?);
+
+        let nearby = Location::new(-6.182_340_3, 106.842_870_4);
+        let cached = cache.get(&nearby).map(|times| times.dohr);
+        assert_eq!(cached, Some(expected_time(11, 54, 14)?));
+
+        let bandung = Location::new(-6.917_464, 107.619_125);
+        assert!(!cache.contains_key(&bandung));
+        Ok(())
+    }
+    #[test]
+    fn method_round_trip() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+
+        assert_eq!(prayer_times.method(), Method::Singapore);
+        assert_eq!(
+            prayer_times.method().name(),
+            "Islamic Religious Council of Singapore"
+        );
+        Ok(())
+    }
+    #[test]
+    fn asr_secondary() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let shafi = prayer_times(config)?;
+        let hanafi = prayer_times(config.with(Method::Singapore, Madhab::Hanafi))?;
+
+        assert!(shafi.asr_secondary > shafi.asr);
+        assert!(shafi.asr_secondary < shafi.maghreb);
+        assert_eq!(shafi.asr_secondary, hanafi.asr);
+        assert!(hanafi.asr_secondary > hanafi.asr);
+        Ok(())
+    }
+    #[test]
+    fn hanafi_asr_later() -> Result<(), crate::Error> {
+        let shafi = prayer_times(Config::new().with(Method::Singapore, Madhab::Shafi))?;
+        let hanafi = prayer_times(Config::new().with(Method::Singapore, Madhab::Hanafi))?;
+        let delay = hanafi.asr - shafi.asr;
+        assert_eq!(hanafi.asr, expected_time(16, 13, 3)?);
+
+        assert!(delay > Duration::minutes(45) && delay < Duration::minutes(90));
+        assert_eq!(hanafi.dohr, shafi.dohr);
+        Ok(())
+    }
+    #[test]
+    fn elevation() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let sea_level = prayer_times(config)?;
+        let mountain = city()?.with_elevation(1000.0);
+        let high = PrayerTimes::new(date()?, mountain, config)?;
+
+        assert!(high.sherook < sea_level.sherook - Duration::minutes(3));
+        assert!(high.maghreb > sea_level.maghreb + Duration::minutes(3));
+        assert_eq!(high.dohr, sea_level.dohr);
+        Ok(())
+    }
+    #[test]
+    fn sunnah() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+        let sunnah = prayer_times.sunnah();
+
+        assert_eq!(sunnah.midnight, prayer_times.midnight);
+        assert_eq!(
+            sunnah.first_third_of_night,
+            prayer_times.first_third_of_night
+        );
+        assert_eq!(sunnah.last_third_of_night, prayer_times.last_third_of_night);
+        assert_eq!(sunnah.tahajjud, prayer_times.tahajjud_window());
+        Ok(())
+    }
+    #[test]
+    fn asr_with_sun_overhead() -> Result<(), crate::Error> {
+        // On the equator at the March equinox the sun passes through the zenith.
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let location = Location::new(0.0, 105.0);
+        let prayer_times = PrayerTimes::new(time::date(2021, 3, 20)?, location, config)?;
+        let after_dohr = prayer_times.asr - prayer_times.dohr;
+
+        assert!(after_dohr > Duration::minutes(170) && after_dohr < Duration::minutes(190));
+        assert!(prayer_times.asr < prayer_times.maghreb);
+        Ok(())
+    }
+    #[test]
+    fn from_now() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+        let remaining = prayer_times.from_now(expected_time(17, 50, 0)?);
+
+        assert_eq!(
+            remaining,
+            vec![
+                (Prayer::Maghreb, prayer_times.maghreb),
+                (Prayer::Ishaa, prayer_times.ishaa),
+                (Prayer::Fajr, prayer_times.fajr_tomorrow),
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn summer_time() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let standard = prayer_times(config.with_summer_time(false))?;
+        let summer = prayer_times(config.with_summer_time(true))?;
+
+        for prayer in PRAYERS {
+            assert_eq!(
+                summer.time(prayer) - standard.time(prayer),
+                Duration::hours(1)
+            );
+        }
+        Ok(())
+    }
+    #[test]
+    fn config_timezone() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        // Jakarta derives UTC+7 from its longitude
+        let derived = prayer_times(config)?;
+        let configured = prayer_times(config.with_timezone(7.0))?;
+        assert_eq!(configured.formatted(true), derived.formatted(true));
+
+        let shifted = prayer_times(config.with_timezone(8.0))?;
+        assert_eq!(shifted.dohr - derived.dohr, Duration::hours(1));
+        Ok(())
+    }
+    #[test]
+    fn solar_midnight() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+        let solar_midnight = prayer_times.solar_midnight();
+
+        assert_eq!(solar_midnight - prayer_times.dohr, Duration::hours(12));
+        assert_eq!(solar_midnight, expected_time(23, 54, 14)?);
+        assert_ne!(solar_midnight, prayer_times.midnight);
+        Ok(())
+    }
+    #[test]
+    fn qibla() -> Result<(), crate::Error> {
+        let jakarta = city()?;
+
+        assert_eq!(jakarta.qibla_rounded(1), 295.1);
+        let nearby = Location::new(-6.182341, 106.842873);
+        assert_eq!(nearby.qibla_rounded(1), jakarta.qibla_rounded(1));
+        assert_eq!(jakarta.distance_to_kaaba_rounded(0), 7919.0);
+        Ok(())
+    }
+    #[test]
+    fn qibla_published_bearings() {
+        let cities = [
+            (Location::new(51.5074, -0.1278), 118.99),  // London
+            (Location::new(40.7128, -74.006), 58.48),   // New York
+            (Location::new(35.6762, 139.6503), 293.02), // Tokyo
+        ];
+        for (city, bearing) in cities {
+            assert!((city.qibla() - bearing).abs() < 0.1);
+        }
+    }
+    #[test]
+    fn qibla_edge_cases() {
+        assert!(Location::new(21.4225, 39.8262).qibla().is_finite());
+        // due north and due south of the Kaaba, on its meridian
+        assert!((Location::new(50.0, 39.826206).qibla() - 180.0).abs() < 0.01);
+        let north = Location::new(0.0, 39.826206).qibla();
+        assert!(!(0.01..=359.99).contains(&north));
+
+        let antipode = Location::new(-21.4225, -140.1738);
+        assert!((0.0..360.0).contains(&antipode.qibla()));
+        assert!((antipode.qibla_distance() - 20015.0).abs() < 1.0);
+    }
+    #[test]
+    fn on_dates() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let dates = [
+            time::date(2021, 4, 16)?,
+            time::date(2021, 1, 1)?,
+            time::date(2021, 9, 3)?,
+        ];
+        let schedule = PrayerSchedule::new(city()?)?.with_config(config);
+        let prayer_times = schedule.on_dates(&dates)?;
+
+        assert_eq!(prayer_times.len(), 3);
+        for (times, date) in prayer_times.iter().zip(dates) {
+            assert_eq!(times.date.date(), date);
+        }
+        Ok(())
+    }
+    #[test]
+    fn strict_ordering() -> Result<(), crate::Error> {
+        // Ishaa falls after midnight at this latitude around the summer solstice
+        let config = Config::new()
+            .with(Method::MuslimWorldLeague, Madhab::Shafi)
+            .with_timezone(2.0);
+        let date = time::date(2021, 6, 21)?;
+        let location = Location::new(48.0, 2.35);
+
+        assert!(PrayerTimes::new(date, location, config).is_ok());
+        assert_eq!(
+            PrayerTimes::new(date, location, config.strict_ordering(true)).err(),
+            Some(crate::Error::NonMonotonicTimes)
+        );
+        Ok(())
+    }
+    #[test]
+    fn sun_azimuths() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times_with_date(config, time::date(2021, 3, 20)?)?;
+
+        assert!((prayer_times.sunrise_azimuth()? - 90.0).abs() < 1.0);
+        assert!((prayer_times.sunset_azimuth()? - 270.0).abs() < 1.0);
+        Ok(())
+    }
+    #[test]
+    fn current_progress() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+        let midpoint = prayer_times.dohr + (prayer_times.asr - prayer_times.dohr) / 2;
+
+        assert!((prayer_times.current_progress(midpoint)? - 0.5).abs() < 0.001);
+        assert_eq!(prayer_times.current_progress(prayer_times.dohr)?, 0.0);
+        Ok(())
+    }
+    #[test]
+    fn asr_preferred() -> Result<(), crate::Error> {
+        let config = Config::new()
+            .with(Method::Singapore, Madhab::Shafi)
+            .with_asr_preferred_offset(30);
+        let prayer_times = prayer_times(config)?;
+        let preferred = prayer_times.asr_preferred();
+
+        assert_eq!(preferred - prayer_times.asr, Duration::minutes(30));
+        assert_eq!(prayer_times.current_time(preferred), Some(Prayer::Asr));
+        Ok(())
+    }
+    #[cfg(feature = "tz")]
+    #[test]
+    fn in_zone() -> Result<(), crate::Error> {
+        use chrono::Offset;
+
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+        let zoned = prayer_times.in_zone("Asia/Jakarta")?;
+
+        for (prayer, time) in zoned {
+            assert_eq!(time.offset().fix().local_minus_utc(), 7 * 3600);
+            assert_eq!(time.naive_local(), prayer_times.time(prayer));
+        }
+        assert!(prayer_times.in_zone("Asia/Nowhere").is_err());
+        Ok(())
+    }
+    #[test]
+    fn day_and_night_add_up() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        for latitude in (-60..=60).step_by(5) {
+            for month in 1..=12 {
+                let location = Location::new(latitude as f32, 106.84287154);
+                let date = time::date(2021, month, 21)?;
+                let Ok(prayer_times) = PrayerTimes::new(date, location, config) else {
+                    continue;
+                };
+                let day = prayer_times.daylight_duration() + prayer_times.night_duration();
+                // off a day by how much earlier or later the sun rises tomorrow
+                assert!((day - Duration::days(1)).num_minutes().abs() < 5);
+
+                let tomorrow = PrayerTimes::new(date + Duration::days(1), location, config)?;
+                assert_eq!(prayer_times.sherook_tomorrow, tomorrow.sherook);
+            }
+        }
+        Ok(())
+    }
+    #[test]
+    fn by_time() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+        let by_time = prayer_times.by_time();
+
+        assert!(by_time.values().copied().eq(PRAYERS));
+        assert!(by_time
+            .keys()
+            .copied()
+            .eq(PRAYERS.map(|prayer| prayer_times.time(prayer))));
+        Ok(())
+    }
+    #[test]
+    fn window_padded() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+        let pad = Duration::minutes(2);
+        let window = prayer_times.window_padded(Prayer::Dohr, pad);
+
+        assert_eq!(window.start, prayer_times.dohr - pad);
+        assert_eq!(window.end, prayer_times.asr + pad);
+        Ok(())
+    }
+    #[test]
+    fn from_day_number() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::MuslimWorldLeague, Madhab::Shafi);
+        let prayer_times = PrayerTimes::from_day_number(0, Location::new(45.0, 105.0), config)?;
+
+        assert_eq!(prayer_times.date.date(), time::date(1970, 1, 1)?);
+        assert!(prayer_times.fajr < prayer_times.dohr && prayer_times.dohr < prayer_times.ishaa);
+        assert!(PrayerTimes::from_day_number(i64::MAX, city()?, config).is_err());
+        Ok(())
+    }
+    #[test]
+    fn extremes() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let schedule = PrayerSchedule::new(city()?)?.with_config(config);
+        let (earliest, latest) = schedule.extremes(2021, Prayer::Fajr)?;
+
+        assert!(earliest.time() < latest.time());
+        // Fajr comes latest in the southern winter
+        assert!((6..=8).contains(&latest.month()));
+        Ok(())
+    }
+    #[test]
+    fn prefer_angle_isha() -> Result<(), crate::Error> {
+        let angle = prayer_times(Config::new().with(Method::Singapore, Madhab::Shafi))?;
+        let config = Config::new()
+            .with(Method::UmmAlQura, Madhab::Shafi)
+            .angle(20.0, 18.0)
+            .prefer_angle_isha(true);
+
+        assert_eq!(prayer_times(config)?.ishaa, angle.ishaa);
+        Ok(())
+    }
+    #[test]
+    fn delta_to_tomorrow() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::MuslimWorldLeague, Madhab::Shafi);
+        let location = Location::new(51.5, 106.8);
+        let prayer_times = PrayerTimes::new(time::date(2021, 3, 20)?, location, config)?;
+
+        // mornings get earlier quickly around the March equinox
+        assert_eq!(prayer_times.delta_to_tomorrow(Prayer::Fajr)?, -3);
+        assert!(prayer_times.delta_to_tomorrow(Prayer::Maghreb)? > 0);
+        Ok(())
+    }
+    #[test]
+    fn countdown_display() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+        let countdown = prayer_times.countdown(expected_time(14, 10, 0)?)?;
+
+        assert_eq!(countdown.to_string(), "Asr in 01:02:14");
+        Ok(())
+    }
+    #[test]
+    fn double_precision_near_60_degrees() -> Result<(), crate::Error> {
+        // Helsinki, against the same formulas evaluated in double precision
+        let config = Config::new()
+            .with(Method::MuslimWorldLeague, Madhab::Shafi)
+            .with_timezone(2.0);
+        let date = time::date(2021, 3, 20)?;
+        let sun = Sun::new(
+            date.and_hms_opt(0, 0, 0).ok_or(crate::Error::InvalidTime)?,
+            config,
+        )?;
+        assert!((sun.declination - 0.042_560_003_190_685_27).abs() < 1e-9);
+        assert!((sun.equation_of_time - 7.519_333_708_160_482).abs() < 1e-9);
+
+        let prayer_times = PrayerTimes::new(date, Location::new(60.1699, 24.9384), config)?;
+        assert_eq!(prayer_times.fajr, expected_time_with_date(date, 3, 53, 45)?);
+        assert_eq!(
+            prayer_times.dohr,
+            expected_time_with_date(date, 12, 27, 45)?
+        );
+        Ok(())
+    }
+    #[test]
+    fn custom_angles_between_methods() -> Result<(), crate::Error> {
+        let custom = prayer_times(Config::new().with_custom_angles(18.5, 16.5))?;
+        let shallow = prayer_times(Config::new().with(Method::NorthAmerica, Madhab::Shafi))?;
+        let deep = prayer_times(Config::new().angle(19.5, 19.5))?;
+
+        assert!(deep.fajr < custom.fajr && custom.fajr < shallow.fajr);
+        assert!(shallow.ishaa < custom.ishaa && custom.ishaa < deep.ishaa);
+        Ok(())
+    }
+    #[test]
+    fn hours_to_time_wraps_around() -> Result<(), crate::Error> {
+        let date = expected_time(0, 0, 0)?;
+        let config = Config::new();
+
+        let before_midnight = PrayerTimes::hours_to_time(date, -0.5, 0.0, config)?;
+        assert_eq!(before_midnight, expected_time(23, 30, 0)?);
+        let shifted = PrayerTimes::hours_to_time(date, 0.25, -1800.0, config)?;
+        assert_eq!(shifted, expected_time(23, 45, 0)?);
+        let past_midnight = PrayerTimes::hours_to_time(date, 25.25, 0.0, config)?;
+        assert_eq!(past_midnight, expected_time(1, 15, 0)?);
+        assert_eq!(
+            PrayerTimes::hours_to_time(date, f32::NAN, 0.0, config),
+            Err(crate::Error::InvalidTime)
+        );
+        Ok(())
+    }
+    #[test]
+    fn zero_shadow_factor() -> Result<(), crate::Error> {
+        let config = Config::new()
+            .with(Method::Singapore, Madhab::Shafi)
+            .with_shadow_factor(0);
+        let prayer_times = prayer_times(config)?;
+
+        assert!((prayer_times.asr - prayer_times.dohr).num_seconds().abs() <= 1);
+        Ok(())
+    }
+    #[test]
+    fn checked_coordinates() -> Result<(), crate::Error> {
+        Location::try_new(90.0, 180.0)?;
+        Location::try_new(-90.0, -180.0)?;
+
+        assert_eq!(
+            Location::try_new(200.0, 10.0),
+            Err(crate::Error::InvalidCoordinates {
+                latitude: 200.0,
+                longitude: 10.0
+            })
+        );
+        assert!(Location::try_new(10.0, -180.5).is_err());
+        assert!(Location::try_new(f32::NAN, 10.0).is_err());
+        Ok(())
+    }
+    #[test]
+    fn location_coordinates() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let location = prayer_times(config)?.location;
+
+        assert_eq!(location.latitude(), -6.18233995);
+        assert_eq!(location.longitude(), 106.84287154);
+        Ok(())
+    }
+    #[test]
+    fn warnings() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::MuslimWorldLeague, Madhab::Shafi);
+        let (_, warnings) = PrayerTimes::new_with_warnings(date()?, city()?, config)?;
+        assert!(warnings.is_empty());
+
+        let summer = time::date(2021, 6, 21)?;
+        let location = Location::new(48.0, 2.35);
+        let config = config.with_timezone(2.0);
+        let (_, warnings) = PrayerTimes::new_with_warnings(summer, location, config)?;
+        assert!(warnings.contains(&Warning::IshaaNearMidnight));
+        // Ishaa past midnight still leaves Maghreb a long window
+        assert!(!warnings.contains(&Warning::ShortWindow(Prayer::Maghreb)));
+        assert!(!warnings.contains(&Warning::HighLatitude));
+
+        let north = Location::new(50.0, 2.35);
+        let rule = config.with_high_latitude_rule(HighLatitudeRule::SeventhOfNight);
+        let (_, warnings) = PrayerTimes::new_with_warnings(summer, north, rule)?;
+        assert!(warnings.contains(&Warning::HighLatitude));
+        Ok(())
+    }
+    #[test]
+    fn muslim_world_league_timetable() -> Result<(), crate::Error> {
+        // London, from the PrayTimes.org algorithm with the MWL angles
+        let config = Config::new()
+            .with(Method::MuslimWorldLeague, Madhab::Shafi)
+            .with_timezone(0.0);
+        let location = Location::new(51.5074, -0.1278);
+        let prayer_times = PrayerTimes::new(time::date(2021, 3, 20)?, location, config)?;
+        let timetable = [(4, 9), (6, 3), (12, 8), (15, 26), (18, 14), (20, 1)];
+
+        assert_timetable(&prayer_times, timetable)
+    }
+    #[test]
+    fn recompute_night() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let mut times = prayer_times(config)?;
+        times.config = config.angle(18.0, 18.0);
+        times.recompute_night()?;
+        let expected = prayer_times(config.angle(18.0, 18.0))?;
+
+        assert_eq!(times.fajr, expected.fajr);
+        assert_eq!(times.fajr_tomorrow, expected.fajr_tomorrow);
+        assert_eq!(times.first_third_of_night, expected.first_third_of_night);
+        assert_eq!(times.midnight, expected.midnight);
+        assert_eq!(times.last_third_of_night, expected.last_third_of_night);
+        assert_ne!(times.midnight, expected_time(23, 15, 24)?);
+        Ok(())
+    }
+    #[test]
+    fn north_america_timetable() -> Result<(), crate::Error> {
+        // New York, from the PrayTimes.org algorithm with the ISNA angles
+        let config = Config::new()
+            .with(Method::NorthAmerica, Madhab::Shafi)
+            .with_timezone(-4.0);
+        let location = Location::new(40.7128, -74.006);
+        let prayer_times = PrayerTimes::new(time::date(2021, 6, 1)?, location, config)?;
+        let timetable = [(3, 50), (5, 27), (12, 54), (16, 53), (20, 21), (21, 58)];
+
+        assert_timetable(&prayer_times, timetable)
+    }
+    #[test]
+    fn sunset_angle() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+        let date = prayer_times.date;
+        let sun = Sun::new(date, config)?;
+        let dohr_time = PrayerTimes::dohr(date, sun, city()?, config)?;
+        let offset = PrayerTimes::time_for_angle(SUNSET_ANGLE, sun, city()?)
+            .ok_or(crate::Error::InvalidTime)?;
+
+        assert_eq!(SUNSET_ANGLE, 90.83333);
+        assert_eq!(
+            PrayerTimes::hours_to_time(date, dohr_time + offset, 0.0, config)?,
+            prayer_times.maghreb
+        );
+        Ok(())
+    }
+    #[test]
+    fn egyptian_timetable() -> Result<(), crate::Error> {
+        // Cairo, from the PrayTimes.org algorithm with the Egyptian angles
+        let config = Config::new()
+            .with(Method::Egyptian, Madhab::Shafi)
+            .with_timezone(2.0);
+        let location = Location::new(30.0444, 31.2357);
+        let prayer_times = PrayerTimes::new(time::date(2021, 6, 1)?, location, config)?;
+        let timetable = [(3, 10), (4, 54), (11, 53), (15, 29), (18, 52), (20, 23)];
+        assert_timetable(&prayer_times, timetable)?;
+
+        // no Ramadan interval, Ishaa still follows the angle
+        let ramadan = PrayerTimes::new(time::date(2021, 4, 20)?, location, config)?;
+        let after_maghreb = ramadan.ishaa - ramadan.maghreb;
+        assert!(after_maghreb > Duration::minutes(80) && after_maghreb < Duration::minutes(90));
+        Ok(())
+    }
+    #[test]
+    fn starting_soon() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+        let now = prayer_times.asr - Duration::minutes(3);
+
+        assert_eq!(
+            prayer_times.starting_soon(now, Duration::minutes(5)),
+            Some(Prayer::Asr)
+        );
+        assert_eq!(prayer_times.starting_soon(now, Duration::minutes(2)), None);
+        Ok(())
+    }
+    #[test]
+    fn tehran_timetable() -> Result<(), crate::Error> {
+        // Tehran, from the PrayTimes.org algorithm with the Tehran angles
+        let config = Config::new()
+            .with(Method::Tehran, Madhab::Shafi)
+            .with_timezone(3.5);
+        let location = Location::new(35.6892, 51.389);
+        let prayer_times = PrayerTimes::new(time::date(2021, 1, 15)?, location, config)?;
+        let timetable = [(5, 45), (7, 14), (12, 14), (14, 55), (17, 34), (18, 24)];
+
+        assert_timetable(&prayer_times, timetable)
+    }
+    #[test]
+    fn jafari_timetable() -> Result<(), crate::Error> {
+        // Qom, from the PrayTimes.org algorithm with the Jafari angles
+        let config = Config::new()
+            .with(Method::Jafari, Madhab::Shafi)
+            .with_timezone(3.5);
+        let location = Location::new(34.6401, 50.8764);
+        let prayer_times = PrayerTimes::new(time::date(2021, 1, 15)?, location, config)?;
+        let timetable = [(5, 55), (7, 13), (12, 16), (15, 0), (17, 36), (18, 27)];
+
+        assert_timetable(&prayer_times, timetable)
+    }
+    #[cfg(feature = "tz")]
+    #[test]
+    fn fajr_tomorrow_across_dst() -> Result<(), crate::Error> {
+        // Europe/London moves to summer time on 2021-03-28
+        let config = Config::new()
+            .with(Method::MuslimWorldLeague, Madhab::Shafi)
+            .with_zone(chrono_tz::Europe::London);
+        let location = Location::new(51.5074, -0.1278);
+        let prayer_times = PrayerTimes::new(time::date(2021, 3, 27)?, location, config)?;
+        let shift = prayer_times.fajr_tomorrow - prayer_times.fajr - Duration::days(1);
+
+        // one hour later on the clock, minus the few minutes Fajr advances daily
+        assert!(shift > Duration::minutes(55) && shift < Duration::minutes(60));
+        assert_timetable(
+            &prayer_times,
+            [(3, 51), (5, 47), (12, 6), (15, 33), (18, 26), (20, 15)],
+        )
+    }
+    #[cfg(feature = "tz")]
+    #[test]
+    fn zone_offset_follows_the_date() -> Result<(), crate::Error> {
+        use chrono::Timelike;
+
+        let config = Config::new().with(Method::MuslimWorldLeague, Madhab::Shafi);
+        // Europe/London moves to summer time on 2021-03-28
+        let london = Location::new(51.5074, -0.1278);
+        let config_london = config.with_zone(chrono_tz::Europe::London);
+        let before = PrayerTimes::new(time::date(2021, 3, 27)?, london, config_london)?;
+        let after = PrayerTimes::new(time::date(2021, 3, 29)?, london, config_london)?;
+        assert_eq!(before.dohr.time().hour(), 12);
+        assert_eq!(after.dohr.time().hour(), 13);
+
+        // Moscow moved its standard time from UTC+4 to UTC+3 on 2014-10-26
+        let moscow = Location::new(55.7558, 37.6173);
+        let config_moscow = config.with_zone(chrono_tz::Europe::Moscow);
+        let before = PrayerTimes::new(time::date(2014, 10, 25)?, moscow, config_moscow)?;
+        let after = PrayerTimes::new(time::date(2014, 10, 27)?, moscow, config_moscow)?;
+        assert_eq!(before.dohr.time().hour(), 13);
+        assert_eq!(after.dohr.time().hour(), 12);
+        Ok(())
+    }
+    #[test]
+    fn window_percentages() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let percentages = prayer_times(config)?.window_percentages();
+        let total: f32 = percentages.iter().map(|&(_, fraction)| fraction).sum();
+
+        assert_eq!(percentages.len(), 6);
+        assert!((total - 1.0).abs() < 0.001);
+
+        // Paris in June, where Ishaa falls past midnight
+        let config = Config::new()
+            .with(Method::MuslimWorldLeague, Madhab::Shafi)
+            .with_timezone(2.0);
+        let paris = Location::new(48.0, 2.35);
+        let summer = PrayerTimes::new(time::date(2021, 6, 21)?, paris, config)?;
+        assert!(summer.ishaa < summer.maghreb);
+        let percentages = summer.window_percentages();
+        let total: f32 = percentages.iter().map(|&(_, fraction)| fraction).sum();
+
+        assert!(percentages.iter().all(|&(_, fraction)| fraction >= 0.0));
+        assert!((total - 1.0).abs() < 0.001);
+        Ok(())
+    }
+    #[test]
+    fn maghreb_angle() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::UmmAlQura, Madhab::Shafi);
+        let sunset = prayer_times(config)?;
+        let delayed = prayer_times(config.with_maghreb_angle(4.0))?;
+
+        assert!(delayed.maghreb > sunset.maghreb + Duration::minutes(10));
+        // the interval Ishaa and the night follow Maghreb
+        assert_eq!(
+            delayed.ishaa - delayed.maghreb,
+            sunset.ishaa - sunset.maghreb
+        );
+        assert!(delayed.first_third_of_night > sunset.first_third_of_night);
+        assert!(delayed.midnight > sunset.midnight);
+        assert!(delayed.last_third_of_night > sunset.last_third_of_night);
+        Ok(())
+    }
+    #[test]
+    fn location_timezone() -> Result<(), crate::Error> {
+        assert_eq!(Location::new(-6.2, 106.8).timezone(), 7.0);
+        assert_eq!(Location::new(51.5, -0.1).timezone(), 0.0);
+        assert_eq!(Location::with_timezone(19.1, 72.9, 5.5).timezone(), 5.5);
+
+        // the offset on the location and on the config give the same times
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let location = Location::with_timezone(51.5, -0.1, 0.0);
+        let on_location = PrayerTimes::new(date()?, location, config)?;
+        let location = Location::new(51.5, -0.1);
+        let on_config = PrayerTimes::new(date()?, location, config.with_timezone(0.0))?;
+        assert_eq!(on_location.formatted(true), on_config.formatted(true));
+        Ok(())
+    }
+    #[test]
+    fn independent_of_machine_timezone() -> Result<(), crate::Error> {
+        // only the location's offset moves the clock, never the machine's
+        let config = Config::new().with(Method::MuslimWorldLeague, Madhab::Shafi);
+        let date = time::date(2021, 3, 20)?;
+        let utc = PrayerTimes::new(date, Location::with_timezone(51.5074, -0.1278, 0.0), config)?;
+        let shifted =
+            PrayerTimes::new(date, Location::with_timezone(51.5074, -0.1278, 3.5), config)?;
+
+        for prayer in PRAYERS {
+            assert_eq!(
+                shifted.time(prayer) - utc.time(prayer),
+                Duration::minutes(210)
+            );
+        }
+        Ok(())
+    }
+    #[test]
+    fn current_ends_at() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+        let mid_maghreb = prayer_times.maghreb + Duration::minutes(30);
+
+        assert_eq!(
+            prayer_times.current_ends_at(mid_maghreb)?,
+            prayer_times.ishaa
+        );
+        let late = prayer_times.ishaa + Duration::hours(2);
+        assert_eq!(
+            prayer_times.current_ends_at(late)?,
+            prayer_times.fajr_tomorrow
+        );
+        Ok(())
+    }
+    #[test]
+    fn in_utc() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let utc = prayer_times(config.is_summer(true))?.in_utc();
+        let yesterday = time::date(2021, 4, 8)?;
+        assert_eq!(utc.fajr, expected_time_with_date(yesterday, 21, 36, 34)?);
+        assert_eq!(utc.dohr, expected_time(4, 54, 14)?);
+        // the last third of the night starts past local midnight, on the next UTC day
+        let utc = prayer_times(config)?.in_utc();
+        assert_eq!(utc.last_third_of_night, expected_time(18, 2, 28)?);
+        // Kashgar keeps Beijing time, so Ishaa falls past local midnight in summer
+        let kashgar = Location::with_timezone(39.4704, 75.9898, 8.0);
+        let summer = PrayerTimes::new(time::date(2021, 6, 21)?, kashgar, config)?;
+        assert_eq!(
+            summer.in_utc().ishaa,
+            expected_time_with_date(time::date(2021, 6, 21)?, 16, 27, 44)?
+        );
+
+        // west of Greenwich, the evening crosses into the next UTC day
+        let config = Config::new().with(Method::NorthAmerica, Madhab::Shafi);
+        let location = Location::with_timezone(40.7128, -74.006, -4.0);
+        let new_york = PrayerTimes::new(time::date(2021, 6, 1)?, location, config)?;
+        let utc = new_york.in_utc();
+        assert_eq!(utc.ishaa.date(), time::date(2021, 6, 2)?);
+        assert_eq!(utc.ishaa - new_york.ishaa, Duration::hours(4));
+        Ok(())
+    }
+    #[test]
+    fn high_latitude_rule() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::MuslimWorldLeague, Madhab::Shafi);
+        let location = Location::with_timezone(65.0, 25.0, 3.0);
+        let date = time::date(2021, 6, 21)?;
+        assert!(PrayerTimes::new(date, location, config).is_err());
+
+        let rule = config.with_high_latitude_rule(HighLatitudeRule::MiddleOfNight);
+        let prayer_times = PrayerTimes::new(date, location, rule)?;
+        // both meet at the middle of the night, a little past solar midnight
+        let solar_midnight = prayer_times.solar_midnight() - Duration::days(1);
+        assert!((prayer_times.fajr - solar_midnight).num_minutes().abs() <= 1);
+        assert!((prayer_times.ishaa - solar_midnight).num_minutes().abs() <= 1);
+        assert!(prayer_times.fajr < prayer_times.sherook);
+
+        let rule = config.with_high_latitude_rule(HighLatitudeRule::SeventhOfNight);
+        let prayer_times = PrayerTimes::new(date, location, rule)?;
+        assert!(prayer_times.fajr < prayer_times.sherook);
+        assert!(prayer_times.sherook - prayer_times.fajr < Duration::hours(1));
+        Ok(())
+    }
+    #[test]
+    fn seventh_of_night() -> Result<(), crate::Error> {
+        // Stockholm, where the sun stays above 18° all night around the solstice
+        let config = Config::new()
+            .with(Method::MuslimWorldLeague, Madhab::Shafi)
+            .with_high_latitude_rule(HighLatitudeRule::SeventhOfNight);
+        let location = Location::with_timezone(59.3293, 18.0686, 2.0);
+        let date = time::date(2021, 6, 21)?;
+        let without_rule = Config::new().with(Method::MuslimWorldLeague, Madhab::Shafi);
+        assert!(PrayerTimes::new(date, location, without_rule).is_err());
+
+        let prayer_times = PrayerTimes::new(date, location, config)?;
+
+        let night = prayer_times.sherook + Duration::days(1) - prayer_times.maghreb;
+        let after_sunset = prayer_times.ishaa - prayer_times.maghreb;
+        let before_sunrise = prayer_times.sherook - prayer_times.fajr;
+        assert!((after_sunset - night / 7).num_seconds().abs() <= 60);
+        assert!((after_sunset - before_sunrise).num_seconds().abs() <= 1);
+        Ok(())
+    }
+    #[test]
+    fn both_asr() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        assert_eq!(prayer_times(config)?.asr_hanafi, None);
+
+        let both = prayer_times(config.with_both_asr(true))?;
+        let hanafi = prayer_times(Config::new().with(Method::Singapore, Madhab::Hanafi))?;
+        assert_eq!(both.asr, expected_time(15, 12, 14)?);
+        assert_eq!(both.asr_hanafi, Some(hanafi.asr));
+        assert!(both.asr_hanafi > Some(both.asr));
+        Ok(())
+    }
+    #[test]
+    fn without_dst() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let summer = prayer_times(config.with_summer_time(true))?;
+        let standard = summer.without_dst()?;
+
+        assert!(!standard.config.is_summer);
+        for prayer in PRAYERS {
+            assert_eq!(
+                summer.time(prayer) - standard.time(prayer),
+                Duration::hours(1)
+            );
+        }
+        Ok(())
+    }
+    #[test]
+    fn no_valid_time() -> Result<(), crate::Error> {
+        // the sun stays above 17° below the horizon all night
+        let config = Config::new().with(Method::MuslimWorldLeague, Madhab::Shafi);
+        let location = Location::with_timezone(65.0, 25.0, 3.0);
+        let result = PrayerTimes::new(time::date(2021, 6, 21)?, location, config);
+
+        assert_eq!(
+            result.err(),
+            Some(crate::Error::NoValidTime {
+                prayer: Prayer::Ishaa
+            })
+        );
+        Ok(())
+    }
+    #[test]
+    fn islamic_day_start() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+
+        assert_eq!(prayer_times.islamic_day_start(), prayer_times.maghreb);
+        Ok(())
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+        let json = serde_json::to_string(&prayer_times).unwrap();
+        let parsed: PrayerTimes = serde_json::from_str(&json).unwrap();
+
+        assert!(json.contains(r#""fajr":"2021-04-09T04:36:34+07:00""#));
+        assert_eq!(parsed.location, prayer_times.location);
+        assert_eq!(parsed.formatted(true), prayer_times.formatted(true));
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+        Ok(())
+    }
+    #[cfg(all(feature = "serde", feature = "tz"))]
+    #[test]
+    fn serde_offset_across_dst() -> Result<(), crate::Error> {
+        // Europe/London moves to summer time on 2021-03-28
+        let config = Config::new()
+            .with(Method::MuslimWorldLeague, Madhab::Shafi)
+            .with_zone(chrono_tz::Europe::London);
+        let location = Location::new(51.5074, -0.1278);
+        let prayer_times = PrayerTimes::new(time::date(2021, 3, 27)?, location, config)?;
+        let json = serde_json::to_string(&prayer_times).unwrap();
+
+        // winter time serializes as `Z`, summer time with its offset
+        assert!(json.contains(r#""fajr":"2021-03-27T03:50:06Z""#));
+        assert!(json.contains(r#""fajr_tomorrow":"2021-03-28T04:47:22+01:00""#));
+        Ok(())
+    }
+    #[test]
+    fn schedule_range() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let schedule = PrayerSchedule::new(city()?)?.with_config(config);
+        let days = schedule
+            .range(time::date(2021, 4, 13)?, time::date(2021, 5, 12)?)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        assert_eq!(days.len(), 30);
+        assert!(days
+            .windows(2)
+            .all(|pair| pair[1].date - pair[0].date == Duration::days(1)));
+        Ok(())
+    }
+    #[test]
+    fn interpolate_depression() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times(config)?;
+
+        assert_eq!(
+            prayer_times.interpolate_depression(config.fajr_angle, true)?,
+            prayer_times.fajr
+        );
+        assert_eq!(
+            prayer_times.interpolate_depression(config.ishaa_angle, false)?,
+            prayer_times.ishaa
+        );
+        // the sun never reaches 18° below the horizon in a London summer,
+        // where only the high latitude rule gives Fajr a time
+        let london = Location::new(51.5074, -0.1278);
+        let rule = config.with_high_latitude_rule(HighLatitudeRule::SeventhOfNight);
+        let summer = PrayerTimes::new(time::date(2021, 6, 21)?, london, rule)?;
+        assert_eq!(
+            summer.interpolate_depression(18.0, true),
+            Err(crate::Error::NoValidTime {
+                prayer: Prayer::Fajr
+            })
+        );
+        Ok(())
+    }
+    #[test]
+    fn imsak_before_fajr() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let times = prayer_times(config)?;
+        assert_eq!(times.fajr - times.imsak, Duration::minutes(10));
+
+        let times = prayer_times(config.with_imsak_minutes(15))?;
+        assert_eq!(times.fajr - times.imsak, Duration::minutes(15));
+        Ok(())
+    }
+    #[test]
+    fn duha_after_sherook() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let times = prayer_times(config)?;
+        assert_eq!(times.duha - times.sherook, Duration::minutes(20));
+
+        let times = prayer_times(config.with_duha_minutes(15))?;
+        assert_eq!(times.duha - times.sherook, Duration::minutes(15));
+        Ok(())
+    }
+    #[test]
+    fn adjusted_dohr() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let adjustments = Adjustments {
+            dohr: 3,
+            ..Adjustments::default()
+        };
+        let times = prayer_times(config)?;
+        let adjusted = prayer_times(config.with_adjustments(adjustments))?;
+
+        assert_eq!(adjusted.dohr - times.dohr, Duration::minutes(3));
+        assert_eq!(adjusted.asr, times.asr);
+        Ok(())
+    }
+    #[test]
+    fn adjusted_asr() -> Result<(), crate::Error> {
+        let config = Config::new()
+            .with(Method::Singapore, Madhab::Shafi)
+            .with_both_asr(true);
+        let adjustments = Adjustments {
+            asr: -4,
+            ..Adjustments::default()
+        };
+        let times = prayer_times(config)?;
+        let adjusted = prayer_times(config.with_adjustments(adjustments))?;
+        let shift = Duration::minutes(-4);
+
+        assert_eq!(adjusted.asr - times.asr, shift);
+        assert_eq!(adjusted.asr_secondary - times.asr_secondary, shift);
+        assert_eq!(
+            adjusted
+                .asr_hanafi
+                .zip(times.asr_hanafi)
+                .map(|(a, b)| a - b),
+            Some(shift)
+        );
+        Ok(())
+    }
+    #[test]
+    fn index_by_prayer() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let times = prayer_times(config)?;
+
+        assert_eq!(times[Prayer::Fajr], times.fajr);
+        assert_eq!(times[Prayer::Sherook], times.sherook);
+        assert_eq!(times[Prayer::Dohr], times.dohr);
+        assert_eq!(times[Prayer::Asr], times.asr);
+        assert_eq!(times[Prayer::Maghreb], times.maghreb);
+        assert_eq!(times[Prayer::Ishaa], times.ishaa);
+        Ok(())
+    }
+    #[test]
+    fn iter_in_order() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let times = prayer_times(config)?;
+        let all: Vec<_> = times.iter().collect();
+
+        assert_eq!(all.len(), 6);
+        assert_eq!(all[0], (Prayer::Fajr, times.fajr));
+        assert!(all.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+        Ok(())
+    }
+    #[test]
+    fn duration_to_next() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let times = prayer_times(config)?;
+
+        let before_fajr = times.fajr - Duration::seconds(90);
+        assert_eq!(times.duration_to_next(before_fajr)?, Duration::seconds(90));
+
+        // after Ishaa, the next prayer is tomorrow's Fajr
+        let remaining = times.duration_to_next(times.ishaa + Duration::minutes(1))?;
+        assert!(remaining > Duration::zero() && remaining < Duration::hours(24));
+        assert_eq!(
+            remaining,
+            times.fajr_tomorrow - times.ishaa - Duration::minutes(1)
+        );
+        Ok(())
+    }
+    #[test]
+    fn current_prayer_before_fajr() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let times = prayer_times(config)?;
+        let night = expected_time(2, 0, 0)?;
+
+        assert_eq!(times.current_time(night), Some(Prayer::Ishaa));
+        assert_eq!(times.current_ends_at(night)?, times.fajr);
+        Ok(())
+    }
+    #[test]
+    fn previous_prayer() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let times = prayer_times(config)?;
+
+        for prayer in PRAYERS {
+            let now = times.time(prayer) + Duration::minutes(1);
+            assert_eq!(times.previous_time(now), prayer);
+        }
+        assert_eq!(times.previous_time(expected_time(2, 0, 0)?), Prayer::Ishaa);
+        assert_eq!(
+            times.previous_time(expected_time(23, 59, 0)?),
+            Prayer::Ishaa
+        );
+        Ok(())
+    }
+    #[test]
+    fn time_remaining_with_clock() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let times = prayer_times(config)?;
+        let clock = FixedClock(times.asr - Duration::minutes(90));
+
+        assert_eq!(times.time_remaining_with(&clock)?, (1, 30));
+
+        let clock = FixedClock(times.asr - Duration::seconds(3725));
+        assert_eq!(times.time_remaining_hms_with(&clock)?, (1, 2, 5));
+        Ok(())
+    }
+    #[test]
+    fn remaining_hms() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let times = prayer_times(config)?;
+
+        let now = times.asr - Duration::seconds(3725);
+        assert_eq!(times.remaining_hms(now)?, (1, 2, 5));
+
+        let (hours, minutes, seconds) = times.remaining_hms(times.ishaa)?;
+        assert!(seconds < 60 && minutes < 60);
+        let total = i64::from(hours * 3600 + minutes * 60 + seconds);
+        assert_eq!(total, (times.fajr_tomorrow - times.ishaa).num_seconds());
+        Ok(())
+    }
+    #[test]
+    fn ishraq_before_duha() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let times = prayer_times(config)?;
+        assert_eq!(times.ishraq - times.sherook, Duration::minutes(15));
+        assert!(times.sherook < times.ishraq && times.ishraq < times.duha);
+
+        let times = prayer_times(config.with_ishraq_minutes(25))?;
+        assert_eq!(times.ishraq - times.sherook, Duration::minutes(25));
+        assert_eq!(times.duha - times.sherook, Duration::minutes(20));
+        Ok(())
+    }
+    #[test]
+    fn csv_row() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let times = prayer_times(config)?;
+        let row = times.to_csv_row();
+        let columns: Vec<&str> = row.split(',').collect();
+
+        assert_eq!(columns.len(), CSV_HEADER.split(',').count());
+        assert_eq!(columns[0], "2021-04-09");
+        for (prayer, column) in PRAYERS.into_iter().zip(&columns[1..]) {
+            let time = chrono::NaiveTime::parse_from_str(column, "%H:%M:%S")
+                .map_err(|err| crate::Error::InvalidArgument(err.to_string()))?;
+            assert_eq!(time, times.time(prayer).time());
+        }
+        Ok(())
+    }
+    #[test]
+    fn month_csv() -> Result<(), crate::Error> {
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let csv = PrayerSchedule::new(city()?)?
+            .with_config(config)
+            .month_csv(2021, 2)?;
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 1 + 28);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[28].starts_with("2021-02-28,"));
+        Ok(())
+    }
+    #[test]
+    fn current_prayer_is_dohr() -> Result<(), crate::Error> {
+        // Dohr is: 2021-04-19T11:51:45+07:00
+        let date = time::date(2021, 4, 19)?;
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times_with_date(config, date)?;
+        let current_prayer_time = expected_time_with_date(date, 11, 52, 0)?;
+
+        assert_eq!(
+            prayer_times.current_time(current_prayer_time),
+            Some(Prayer::Dohr)
+        );
+        Ok(())
+    }
+    #[test]
+    fn current_prayer_is_asr() -> Result<(), crate::Error> {
+        // Asr is: 2021-04-19T15:11:51+07:00
+        let date = time::date(2021, 4, 19)?;
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times_with_date(config, date)?;
+        let current_prayer_time = expected_time_with_date(date, 15, 13, 0)?;
+
+        assert_eq!(
+            prayer_times.current_time(current_prayer_time),
+            Some(Prayer::Asr)
+        );
+        Ok(())
+    }
+    #[test]
+    fn current_prayer_is_maghreb() -> Result<(), crate::Error> {
+        // Maghreb is: 2021-04-19T17:50:12+07:00
+        let date = time::date(2021, 4, 19)?;
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times_with_date(config, date)?;
+        let current_prayer_time = expected_time_with_date(date, 17, 51, 0)?;
+
+        assert_eq!(
+            prayer_times.current_time(current_prayer_time),
+            Some(Prayer::Maghreb)
+        );
+        Ok(())
+    }
+    #[test]
+    fn current_prayer_is_ishaa() -> Result<(), crate::Error> {
+        // Ishaa is: 2021-04-19T19:00:27+07:00
+        let date = time::date(2021, 4, 19)?;
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times_with_date(config, date)?;
+        let current_prayer_time = expected_time_with_date(date, 19, 1, 0)?;
+
+        assert_eq!(
+            prayer_times.current_time(current_prayer_time),
+            Some(Prayer::Ishaa)
+        );
+        Ok(())
+    }
+    #[test]
+    fn current_prayer_is_fajr() -> Result<(), crate::Error> {
+        // Fajr is: 2021-04-19T04:34:54+07:00,
+        let date = time::date(2021, 4, 19)?;
+        let config = Config::new().with(Method::Singapore, Madhab::Shafi);
+        let prayer_times = prayer_times_with_date(config, date)?;
+        let current_prayer_time = expected_time_with_date(date, 4, 35, 0)?;
+
+        assert_eq!(
+            prayer_times.current_time(current_prayer_time),
+            Some(Prayer::Fajr)
+        );
         Ok(())
     }
     #[test]
@@ -510,7 +3414,7 @@ mod tests {
 
         assert_eq!(
             prayer_times.current_time(current_prayer_time),
-            Prayer::Sherook
+            Some(Prayer::Sherook)
         );
         Ok(())
     }
@@ -1,29 +1,77 @@
-use chrono::{Local, NaiveDate, NaiveTime};
+use chrono::{Local, NaiveDate, NaiveDateTime};
 
 use crate::{Date, DateTime};
 
-pub fn now() -> DateTime {
-    Local::now().naive_local()
+/// Source of the current time.
+/// Implement it where the system clock is unavailable, e.g. on WASM.
+pub trait Clock {
+    fn now(&self) -> DateTime;
+    fn today(&self) -> Date {
+        self.now().date()
+    }
 }
 
-pub fn one_sec_before_midnight() -> Option<DateTime> {
-    let midnight = NaiveTime::from_hms_opt(23, 59, 59)?;
-    let date = Local::now().date_naive();
+/// The machine's local clock
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SystemClock;
 
-    Some(date.and_time(midnight))
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime {
+        Local::now().naive_local()
+    }
 }
 
-pub fn midnight() -> Option<DateTime> {
-    let midnight = NaiveTime::from_hms_opt(0, 0, 00)?;
-    let date = Local::now().date_naive();
+/// A clock stopped at the given time
+#[derive(Debug, Copy, Clone)]
+pub struct FixedClock(pub DateTime);
 
-    Some(date.and_time(midnight))
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime {
+        self.0
+    }
 }
 
 pub fn today() -> Date {
-    Local::now().date_naive()
+    SystemClock.today()
+}
+
+/// Convert Unix seconds to a UTC date time
+// `from_timestamp_opt` is deprecated in later chrono releases,
+// but its replacement is missing from the oldest one we support
+#[allow(deprecated)]
+pub fn from_unix(secs: i64) -> Option<DateTime> {
+    NaiveDateTime::from_timestamp_opt(secs, 0)
 }
 
 pub fn date(year: i32, month: u32, day: u32) -> Result<Date, crate::Error> {
     NaiveDate::from_ymd_opt(year, month, day).ok_or(crate::Error::InvalidTime)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_clock() -> Result<(), crate::Error> {
+        let time = date(2021, 4, 9)?
+            .and_hms_opt(12, 0, 0)
+            .ok_or(crate::Error::InvalidTime)?;
+        let clock = FixedClock(time);
+
+        assert_eq!(clock.now(), time);
+        assert_eq!(clock.today(), date(2021, 4, 9)?);
+        Ok(())
+    }
+    #[test]
+    fn unix_epoch() -> Result<(), crate::Error> {
+        let epoch = date(1970, 1, 1)?.and_hms_opt(0, 0, 0);
+        assert_eq!(from_unix(0), epoch);
+
+        let before_epoch = date(1969, 12, 31)?.and_hms_opt(23, 59, 59);
+        assert_eq!(from_unix(-1), before_epoch);
+
+        let jakarta_dawn = date(2021, 4, 8)?.and_hms_opt(20, 0, 0);
+        assert_eq!(from_unix(1_617_912_000), jakarta_dawn);
+        Ok(())
+    }
+}